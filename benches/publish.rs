@@ -0,0 +1,69 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Publish latency of a connection per publish versus the persistent
+//! connection of `RedisTransport`.
+//!
+//! Requires a Redis server, at `REDIS_URL` or `redis://127.0.0.1:6379`:
+//!
+//! ```text
+//! cargo bench --bench publish
+//! ```
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use redis::AsyncCommands;
+use redis_watcher::{RedisTransport, Transport};
+use tokio::runtime::Runtime;
+
+const CHANNEL: &str = "/casbin/bench";
+const PAYLOAD: &[u8] = br#"{"Method":"UpdateForAddPolicy","ID":"bench","Sec":"p","Ptype":"p","NewRule":["alice","data1","read"]}"#;
+
+fn redis_url() -> String {
+    std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string())
+}
+
+fn bench_publish(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let url = redis_url();
+    let client = redis::Client::open(url.as_str()).unwrap();
+    if runtime
+        .block_on(client.get_multiplexed_async_connection())
+        .is_err()
+    {
+        eprintln!(
+            "Skipping publish benchmarks - Redis not available at {}",
+            url
+        );
+        return;
+    }
+
+    let mut group = c.benchmark_group("publish");
+    group.bench_function("connection_per_publish", |b| {
+        b.to_async(&runtime).iter(|| async {
+            let mut conn = client.get_multiplexed_async_connection().await.unwrap();
+            let _: usize = conn.publish(CHANNEL, PAYLOAD).await.unwrap();
+        })
+    });
+
+    let transport = RedisTransport::standalone(&url).unwrap();
+    group.bench_function("persistent_connection", |b| {
+        b.to_async(&runtime).iter(|| async {
+            transport.publish(CHANNEL, PAYLOAD.to_vec()).await.unwrap();
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_publish);
+criterion_main!(benches);
@@ -0,0 +1,146 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Batching of several published messages into one payload.
+//!
+//! Each message is serialized and sealed on its own, then the payloads are
+//! concatenated behind a single header line listing their lengths:
+//!
+//! ```text
+//! BATCH <length> <length> ...\n<payload><payload>...
+//! ```
+//!
+//! A batch is chunked like any other payload when it is too large.
+
+use crate::options::BatchingOptions;
+use crate::{Result, WatcherError};
+
+const SCHEME: &str = "BATCH ";
+
+/// Batches holding more messages than this are rejected
+const MAX_MESSAGES: usize = 16 * 1024;
+
+/// Check that the options can be used to batch messages
+pub(crate) fn validate(batching: &BatchingOptions) -> Result<()> {
+    if batching.max_batch == 0 || batching.max_batch > MAX_MESSAGES {
+        return Err(WatcherError::Configuration(format!(
+            "Batch size must be between 1 and {}, got {}",
+            MAX_MESSAGES, batching.max_batch
+        )));
+    }
+    Ok(())
+}
+
+/// Combine sealed payloads into one batch
+pub(crate) fn frame(payloads: &[Vec<u8>]) -> Vec<u8> {
+    let lengths: Vec<String> = payloads
+        .iter()
+        .map(|payload| payload.len().to_string())
+        .collect();
+    let header = format!("{}{}\n", SCHEME, lengths.join(" "));
+
+    let size = payloads.iter().map(Vec::len).sum::<usize>();
+    let mut batch = Vec::with_capacity(header.len() + size);
+    batch.extend_from_slice(header.as_bytes());
+    for payload in payloads {
+        batch.extend_from_slice(payload);
+    }
+    batch
+}
+
+/// Whether `data` is a batch produced by [`frame`]
+pub(crate) fn is_batch(data: &[u8]) -> bool {
+    data.starts_with(SCHEME.as_bytes())
+}
+
+/// Split a batch into the payloads it holds
+pub(crate) fn unpack(batch: &[u8]) -> Result<Vec<&[u8]>> {
+    let rest = batch
+        .strip_prefix(SCHEME.as_bytes())
+        .ok_or_else(|| invalid("not a batch"))?;
+    let newline = rest
+        .iter()
+        .position(|b| *b == b'\n')
+        .ok_or_else(|| invalid("malformed batch header"))?;
+    let header =
+        std::str::from_utf8(&rest[..newline]).map_err(|_| invalid("malformed batch header"))?;
+
+    let lengths = header
+        .split(' ')
+        .map(|length| length.parse::<usize>())
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|_| invalid("malformed batch header"))?;
+    if lengths.len() > MAX_MESSAGES {
+        return Err(invalid(&format!(
+            "batch of {} messages exceeds {}",
+            lengths.len(),
+            MAX_MESSAGES
+        )));
+    }
+
+    let mut body = &rest[newline + 1..];
+    let mut payloads = Vec::with_capacity(lengths.len());
+    for length in lengths {
+        if length > body.len() {
+            return Err(invalid("batch is shorter than its header"));
+        }
+        let (payload, remaining) = body.split_at(length);
+        payloads.push(payload);
+        body = remaining;
+    }
+    if !body.is_empty() {
+        return Err(invalid("batch is longer than its header"));
+    }
+    Ok(payloads)
+}
+
+fn invalid(reason: &str) -> WatcherError {
+    WatcherError::Codec(reason.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_and_unpack_roundtrip() {
+        let payloads = vec![
+            br#"{"Method":"UpdateForAddPolicy","ID":"a"}"#.to_vec(),
+            b"HMAC-SHA256 k1 sig\n{}".to_vec(),
+            Vec::new(),
+        ];
+        let batch = frame(&payloads);
+        assert!(is_batch(&batch));
+        assert!(!is_batch(&payloads[0]));
+        assert_eq!(unpack(&batch).unwrap(), payloads);
+    }
+
+    #[test]
+    fn test_malformed_batches_are_rejected() {
+        for batch in [
+            &b"BATCH 3 x\nabc"[..],
+            b"BATCH 4\nabc",
+            b"BATCH 2\nabc",
+            b"BATCH 3",
+        ] {
+            assert!(matches!(unpack(batch), Err(WatcherError::Codec(_))));
+        }
+    }
+
+    #[test]
+    fn test_batch_size_is_validated() {
+        assert!(validate(&BatchingOptions::default()).is_ok());
+        assert!(validate(&BatchingOptions::default().with_max_batch(0)).is_err());
+    }
+}
@@ -0,0 +1,403 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Splitting of oversized payloads into chunks.
+//!
+//! Each chunk is published separately, prefixed with a single header line:
+//!
+//! ```text
+//! CHUNK <transfer id> <index> <total>\n<part of the payload>
+//! ```
+//!
+//! Chunking is the outermost layer: the payload is split after it has been
+//! signed, and reassembled before its signature is verified.
+
+use crate::options::ChunkingOptions;
+use crate::{Result, WatcherError};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+const SCHEME: &str = "CHUNK ";
+
+/// Smallest accepted chunk size, leaving room for the header
+const MIN_CHUNK_SIZE: usize = 256;
+
+/// Transfers announcing more chunks than this are rejected
+const MAX_CHUNKS: usize = 16 * 1024;
+
+/// Check that the options can be used to split messages
+pub(crate) fn validate(chunking: &ChunkingOptions) -> Result<()> {
+    if chunking.max_size < MIN_CHUNK_SIZE {
+        return Err(WatcherError::Configuration(format!(
+            "Chunk size must be at least {} bytes, got {}",
+            MIN_CHUNK_SIZE, chunking.max_size
+        )));
+    }
+    if chunking.max_transfers == 0 {
+        return Err(WatcherError::Configuration(
+            "Concurrent chunked transfers must be at least 1".to_string(),
+        ));
+    }
+    if chunking.max_buffered_bytes < chunking.max_size {
+        return Err(WatcherError::Configuration(format!(
+            "Buffered chunk bytes must be at least the chunk size {}, got {}",
+            chunking.max_size, chunking.max_buffered_bytes
+        )));
+    }
+    Ok(())
+}
+
+/// Split `payload` into chunks of at most `max_size` bytes, headers included
+///
+/// Payloads that fit are returned as a single unchanged frame.
+pub(crate) fn split(chunking: &ChunkingOptions, payload: Vec<u8>) -> Vec<Vec<u8>> {
+    if payload.len() <= chunking.max_size {
+        return vec![payload];
+    }
+
+    let transfer_id = Uuid::new_v4().simple().to_string();
+    // Index and total never have more digits than the payload length
+    let digits = payload.len().to_string().len();
+    let overhead = SCHEME.len() + transfer_id.len() + 2 * digits + 3;
+    let parts: Vec<&[u8]> = payload.chunks(chunking.max_size - overhead).collect();
+
+    let total = parts.len();
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(index, part)| {
+            let header = format!("{}{} {} {}\n", SCHEME, transfer_id, index, total);
+            let mut chunk = Vec::with_capacity(header.len() + part.len());
+            chunk.extend_from_slice(header.as_bytes());
+            chunk.extend_from_slice(part);
+            chunk
+        })
+        .collect()
+}
+
+/// Whether `data` is a chunk produced by [`split`]
+pub(crate) fn is_chunk(data: &[u8]) -> bool {
+    data.starts_with(SCHEME.as_bytes())
+}
+
+/// Chunks received so far for one transfer
+struct Transfer {
+    started: Instant,
+    chunks: Vec<Option<Vec<u8>>>,
+    missing: usize,
+    /// Bytes held by the transfer, including its chunk slots and ID
+    size: usize,
+}
+
+/// Reassembles chunked payloads, discarding transfers that take too long
+///
+/// Chunk headers are not authenticated, so the number of transfers and the
+/// bytes they hold are capped: the oldest transfers are evicted to make room.
+pub(crate) struct Reassembler {
+    timeout: Duration,
+    max_transfers: usize,
+    max_buffered_bytes: usize,
+    buffered_bytes: usize,
+    transfers: HashMap<String, Transfer>,
+    evicted: usize,
+}
+
+impl Reassembler {
+    /// Reassembler with the limits of `chunking`, or the defaults without it
+    pub(crate) fn new(chunking: Option<&ChunkingOptions>) -> Self {
+        Self {
+            timeout: chunking.map_or(ChunkingOptions::DEFAULT_REASSEMBLY_TIMEOUT, |chunking| {
+                chunking.reassembly_timeout
+            }),
+            max_transfers: chunking.map_or(ChunkingOptions::DEFAULT_MAX_TRANSFERS, |chunking| {
+                chunking.max_transfers
+            }),
+            max_buffered_bytes: chunking
+                .map_or(ChunkingOptions::DEFAULT_MAX_BUFFERED_BYTES, |chunking| {
+                    chunking.max_buffered_bytes
+                }),
+            buffered_bytes: 0,
+            transfers: HashMap::new(),
+            evicted: 0,
+        }
+    }
+
+    /// Add a chunk, returning the whole payload once every chunk has arrived
+    pub(crate) fn push(&mut self, chunk: &[u8]) -> Result<Option<Vec<u8>>> {
+        let (transfer_id, index, total, part) = parse(chunk)?;
+        if !self.transfers.contains_key(transfer_id) {
+            // The sender chooses the ID, so it counts against the limit as well
+            let slots = total * std::mem::size_of::<Option<Vec<u8>>>() + transfer_id.len();
+            // A transfer that can never fit is dropped without evicting the others
+            if slots + part.len() > self.max_buffered_bytes {
+                self.evicted += 1;
+                return Ok(None);
+            }
+            while self.transfers.len() >= self.max_transfers {
+                self.evict_oldest();
+            }
+            self.buffered_bytes += slots;
+            self.transfers.insert(
+                transfer_id.to_string(),
+                Transfer {
+                    started: Instant::now(),
+                    chunks: vec![None; total],
+                    missing: total,
+                    size: slots,
+                },
+            );
+        }
+        let transfer = self
+            .transfers
+            .get_mut(transfer_id)
+            .expect("transfer exists");
+        if transfer.chunks.len() != total {
+            return Err(invalid(&format!(
+                "chunk count changed within transfer {}",
+                transfer_id
+            )));
+        }
+
+        // Redelivered chunks are ignored
+        if transfer.chunks[index].is_none() {
+            transfer.chunks[index] = Some(part.to_vec());
+            transfer.missing -= 1;
+            transfer.size += part.len();
+            self.buffered_bytes += part.len();
+        }
+        if transfer.missing == 0 {
+            let transfer = self.remove(transfer_id).expect("transfer exists");
+            return Ok(Some(
+                transfer.chunks.into_iter().flatten().flatten().collect(),
+            ));
+        }
+
+        while self.buffered_bytes > self.max_buffered_bytes {
+            self.evict_oldest();
+        }
+        Ok(None)
+    }
+
+    /// Drop transfers older than the timeout, returning how many were dropped
+    pub(crate) fn expire(&mut self) -> usize {
+        let timeout = self.timeout;
+        let expired: Vec<String> = self
+            .transfers
+            .iter()
+            .filter(|(_, transfer)| transfer.started.elapsed() >= timeout)
+            .map(|(transfer_id, _)| transfer_id.clone())
+            .collect();
+        for transfer_id in &expired {
+            self.remove(transfer_id);
+        }
+        expired.len()
+    }
+
+    /// Whether no transfer is waiting for more chunks
+    pub(crate) fn is_empty(&self) -> bool {
+        self.transfers.is_empty()
+    }
+
+    /// Number of transfers evicted to stay within the limits since the last call
+    pub(crate) fn take_evicted(&mut self) -> usize {
+        std::mem::take(&mut self.evicted)
+    }
+
+    fn evict_oldest(&mut self) {
+        let oldest = self
+            .transfers
+            .iter()
+            .min_by_key(|(_, transfer)| transfer.started)
+            .map(|(transfer_id, _)| transfer_id.clone());
+        if let Some(transfer_id) = oldest {
+            self.remove(&transfer_id);
+            self.evicted += 1;
+        }
+    }
+
+    fn remove(&mut self, transfer_id: &str) -> Option<Transfer> {
+        let transfer = self.transfers.remove(transfer_id)?;
+        self.buffered_bytes -= transfer.size;
+        Some(transfer)
+    }
+}
+
+/// Split a chunk into transfer ID, index, total and payload part
+fn parse(chunk: &[u8]) -> Result<(&str, usize, usize, &[u8])> {
+    let rest = chunk
+        .strip_prefix(SCHEME.as_bytes())
+        .ok_or_else(|| invalid("not a chunk"))?;
+    let newline = rest
+        .iter()
+        .position(|b| *b == b'\n')
+        .ok_or_else(|| invalid("malformed chunk header"))?;
+    let header =
+        std::str::from_utf8(&rest[..newline]).map_err(|_| invalid("malformed chunk header"))?;
+
+    let mut fields = header.split(' ');
+    let (Some(transfer_id), Some(index), Some(total), None) =
+        (fields.next(), fields.next(), fields.next(), fields.next())
+    else {
+        return Err(invalid("malformed chunk header"));
+    };
+    let (Ok(index), Ok(total)) = (index.parse::<usize>(), total.parse::<usize>()) else {
+        return Err(invalid("malformed chunk header"));
+    };
+    if transfer_id.is_empty() || index >= total || total > MAX_CHUNKS {
+        return Err(invalid(&format!("invalid chunk {}/{}", index, total)));
+    }
+    Ok((transfer_id, index, total, &rest[newline + 1..]))
+}
+
+fn invalid(reason: &str) -> WatcherError {
+    WatcherError::Chunking(reason.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunking() -> ChunkingOptions {
+        ChunkingOptions::new(MIN_CHUNK_SIZE)
+    }
+
+    #[test]
+    fn test_small_payload_is_not_split() {
+        let chunks = split(&chunking(), b"payload".to_vec());
+        assert_eq!(chunks, vec![b"payload".to_vec()]);
+        assert!(!is_chunk(&chunks[0]));
+    }
+
+    #[test]
+    fn test_split_and_reassemble_out_of_order() {
+        let payload: Vec<u8> = (0..2000u32).map(|i| (i % 251) as u8).collect();
+        let mut chunks = split(&chunking(), payload.clone());
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= MIN_CHUNK_SIZE));
+        assert!(chunks.iter().all(|chunk| is_chunk(chunk)));
+
+        chunks.reverse();
+        let mut reassembler = Reassembler::new(None);
+        let last = chunks.pop().unwrap();
+        for chunk in &chunks {
+            assert_eq!(reassembler.push(chunk).unwrap(), None);
+        }
+        // Duplicates don't complete a transfer
+        assert_eq!(reassembler.push(&chunks[0]).unwrap(), None);
+        assert_eq!(reassembler.push(&last).unwrap(), Some(payload));
+        assert_eq!(reassembler.expire(), 0);
+    }
+
+    #[test]
+    fn test_incomplete_transfers_expire() {
+        let chunks = split(&chunking(), vec![b'x'; 1000]);
+        let chunking = chunking().with_reassembly_timeout(Duration::ZERO);
+        let mut reassembler = Reassembler::new(Some(&chunking));
+        assert_eq!(reassembler.push(&chunks[0]).unwrap(), None);
+        assert_eq!(reassembler.expire(), 1);
+    }
+
+    #[test]
+    fn test_oldest_transfer_is_evicted_beyond_max_transfers() {
+        let chunking = chunking().with_max_transfers(2);
+        let mut reassembler = Reassembler::new(Some(&chunking));
+        for transfer_id in ["a", "b", "c"] {
+            let chunk = format!("CHUNK {} 0 2\npart", transfer_id);
+            assert_eq!(reassembler.push(chunk.as_bytes()).unwrap(), None);
+        }
+        assert_eq!(reassembler.take_evicted(), 1);
+        assert_eq!(reassembler.take_evicted(), 0);
+
+        // "a" was evicted, so its last chunk starts a new transfer
+        assert_eq!(reassembler.push(b"CHUNK a 1 2\npart").unwrap(), None);
+        assert_eq!(reassembler.take_evicted(), 1);
+        assert_eq!(
+            reassembler.push(b"CHUNK c 1 2\n!").unwrap(),
+            Some(b"part!".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_oldest_transfers_are_evicted_beyond_max_buffered_bytes() {
+        let chunking = chunking().with_max_buffered_bytes(2 * MIN_CHUNK_SIZE);
+        let mut reassembler = Reassembler::new(Some(&chunking));
+        let part = vec![b'x'; 150];
+        let chunk = |transfer_id: &str| {
+            let mut chunk = format!("CHUNK {} 0 2\n", transfer_id).into_bytes();
+            chunk.extend_from_slice(&part);
+            chunk
+        };
+        assert_eq!(reassembler.push(&chunk("a")).unwrap(), None);
+        assert_eq!(reassembler.push(&chunk("b")).unwrap(), None);
+        assert_eq!(reassembler.take_evicted(), 0);
+        assert_eq!(reassembler.push(&chunk("c")).unwrap(), None);
+        assert_eq!(reassembler.take_evicted(), 1);
+        assert!(reassembler.buffered_bytes <= 2 * MIN_CHUNK_SIZE);
+
+        // Forged headers announcing many chunks are dropped on their own
+        assert_eq!(reassembler.push(b"CHUNK d 0 16384\n").unwrap(), None);
+        assert_eq!(reassembler.take_evicted(), 1);
+        assert!(!reassembler.transfers.contains_key("d"));
+        assert!(reassembler.transfers.contains_key("c"));
+        assert!(reassembler.buffered_bytes <= 2 * MIN_CHUNK_SIZE);
+    }
+
+    #[test]
+    fn test_transfer_ids_count_against_max_buffered_bytes() {
+        let chunking = chunking().with_max_buffered_bytes(2 * MIN_CHUNK_SIZE);
+        let mut reassembler = Reassembler::new(Some(&chunking));
+        let chunk = |transfer_id: char, len: usize| {
+            let transfer_id: String = std::iter::repeat_n(transfer_id, len).collect();
+            format!("CHUNK {} 0 2\n", transfer_id).into_bytes()
+        };
+
+        // Empty parts, but long IDs
+        for transfer_id in ['a', 'b', 'c'] {
+            assert_eq!(
+                reassembler
+                    .push(&chunk(transfer_id, MIN_CHUNK_SIZE))
+                    .unwrap(),
+                None
+            );
+            assert!(reassembler.buffered_bytes <= 2 * MIN_CHUNK_SIZE);
+        }
+        assert_eq!(reassembler.take_evicted(), 2);
+        assert_eq!(reassembler.transfers.len(), 1);
+
+        // An ID longer than the limit is dropped on its own
+        assert_eq!(
+            reassembler.push(&chunk('d', 2 * MIN_CHUNK_SIZE)).unwrap(),
+            None
+        );
+        assert_eq!(reassembler.take_evicted(), 1);
+        assert_eq!(reassembler.transfers.len(), 1);
+    }
+
+    #[test]
+    fn test_malformed_chunks_are_rejected() {
+        let mut reassembler = Reassembler::new(None);
+        for chunk in [
+            &b"CHUNK abc 0\npayload"[..],
+            b"CHUNK abc 2 2\npayload",
+            b"CHUNK abc x 2\npayload",
+            b"CHUNK abc 0 2",
+        ] {
+            assert!(matches!(
+                reassembler.push(chunk),
+                Err(WatcherError::Chunking(_))
+            ));
+        }
+    }
+}
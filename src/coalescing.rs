@@ -0,0 +1,291 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Coalescing of bursts of received messages.
+//!
+//! Messages are held back until the window opened by the first one closes or
+//! the batch is full, then delivered together. A batch containing a full
+//! reload collapses into a single `Update`, since reloading covers every
+//! other change in it. The per-message callbacks get a single message per
+//! batch, merging several messages into an `Update`.
+
+use crate::options::CoalescingOptions;
+use crate::watcher::{Message, UpdateType};
+use crate::{Result, WatcherError};
+use std::time::{Instant, SystemTime};
+
+/// Check that the options can be used to coalesce messages
+pub(crate) fn validate(coalescing: &CoalescingOptions) -> Result<()> {
+    if coalescing.max_batch == Some(0) {
+        return Err(WatcherError::Configuration(
+            "Coalescing batch size must be at least 1".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// A received message and its payload as handed to the string callback
+pub(crate) type Received = (Message, String);
+
+/// Messages held back until the window closes or the batch is full
+pub(crate) struct Batch {
+    options: CoalescingOptions,
+    received: Vec<Received>,
+    /// When the first pending message was received, and when its window closes
+    started: Option<(Instant, Option<tokio::time::Instant>)>,
+    /// When the earliest pending message with a known publish time was published
+    published_at: Option<SystemTime>,
+}
+
+impl Batch {
+    pub(crate) fn new(options: CoalescingOptions) -> Self {
+        Self {
+            options,
+            received: Vec::new(),
+            started: None,
+            published_at: None,
+        }
+    }
+
+    /// Add a message received at `received_at`, returning whether the batch is full
+    pub(crate) fn push(
+        &mut self,
+        received: Received,
+        received_at: Instant,
+        published_at: Option<SystemTime>,
+    ) -> bool {
+        self.started.get_or_insert_with(|| {
+            // A window too long to represent never closes
+            let deadline = tokio::time::Instant::now().checked_add(self.options.window);
+            (received_at, deadline)
+        });
+        self.published_at = match (self.published_at, published_at) {
+            (Some(earliest), Some(published_at)) => Some(earliest.min(published_at)),
+            (earliest, published_at) => earliest.or(published_at),
+        };
+        self.received.push(received);
+        self.options
+            .max_batch
+            .is_some_and(|max_batch| self.received.len() >= max_batch)
+    }
+
+    /// When the window of the pending messages closes, if it ever does
+    pub(crate) fn deadline(&self) -> Option<tokio::time::Instant> {
+        self.started.and_then(|(_, deadline)| deadline)
+    }
+
+    /// Whether no message is held back
+    pub(crate) fn is_empty(&self) -> bool {
+        self.started.is_none()
+    }
+
+    /// Take the pending messages, collapsed, when the first was received and
+    /// when the earliest was published
+    pub(crate) fn take(&mut self) -> Option<(Vec<Received>, Instant, Option<SystemTime>)> {
+        let (received_at, _) = self.started.take()?;
+        Some((
+            collapse(std::mem::take(&mut self.received)),
+            received_at,
+            self.published_at.take(),
+        ))
+    }
+}
+
+/// Replace a batch containing a full reload by a single `Update`
+///
+/// The `Update` comes from the sender of the last reload in the batch.
+fn collapse(received: Vec<Received>) -> Vec<Received> {
+    let Some(last_reload) = received
+        .iter()
+        .rev()
+        .find(|(message, _)| message.method.is_full_reload())
+    else {
+        return received;
+    };
+    if received.len() == 1 && last_reload.0.method == UpdateType::Update {
+        return received;
+    }
+    vec![reload(last_reload)]
+}
+
+/// The single message handed to the per-message callbacks for a batch
+///
+/// A batch of several messages is merged into an `Update` from the sender of
+/// the last one, since a reload covers all of them.
+pub(crate) fn merge(mut received: Vec<Received>) -> Option<Received> {
+    match received.len() {
+        0 | 1 => received.pop(),
+        _ => received.last().map(reload),
+    }
+}
+
+/// Full reload standing in for `received` and the messages before it
+fn reload((message, payload): &Received) -> Received {
+    let mut reload = Message::new(UpdateType::Update, message.id.clone());
+    reload.sequence = message.sequence;
+    let payload = reload.to_json().unwrap_or_else(|_| payload.clone());
+    (reload, payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn received(method: UpdateType, id: &str) -> Received {
+        let message = Message::new(method, id.to_string());
+        let payload = message.to_json().unwrap();
+        (message, payload)
+    }
+
+    fn methods(received: &[Received]) -> Vec<(UpdateType, String)> {
+        received
+            .iter()
+            .map(|(message, _)| (message.method.clone(), message.id.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_batch_keeps_incremental_updates() {
+        let mut batch = Batch::new(CoalescingOptions::new(Duration::from_secs(1)));
+        assert!(batch.take().is_none());
+        assert!(batch.deadline().is_none());
+
+        assert!(!batch.push(
+            received(UpdateType::UpdateForAddPolicy, "a"),
+            Instant::now(),
+            None
+        ));
+        assert!(!batch.push(
+            received(UpdateType::UpdateForRemovePolicy, "b"),
+            Instant::now(),
+            None
+        ));
+        assert!(batch.deadline().is_some());
+
+        let (taken, _, _) = batch.take().unwrap();
+        assert_eq!(
+            methods(&taken),
+            vec![
+                (UpdateType::UpdateForAddPolicy, "a".to_string()),
+                (UpdateType::UpdateForRemovePolicy, "b".to_string()),
+            ]
+        );
+        assert!(batch.deadline().is_none());
+    }
+
+    #[test]
+    fn test_batch_with_reload_collapses_to_update() {
+        let mut batch = Batch::new(CoalescingOptions::new(Duration::from_secs(1)));
+        batch.push(
+            received(UpdateType::UpdateForAddPolicy, "a"),
+            Instant::now(),
+            None,
+        );
+        batch.push(
+            received(UpdateType::UpdateForSavePolicy, "b"),
+            Instant::now(),
+            None,
+        );
+        batch.push(
+            received(UpdateType::UpdateForAddPolicy, "c"),
+            Instant::now(),
+            None,
+        );
+
+        let (taken, _, _) = batch.take().unwrap();
+        assert_eq!(methods(&taken), vec![(UpdateType::Update, "b".to_string())]);
+        let payload = Message::from_json(&taken[0].1).unwrap();
+        assert_eq!(payload.method, UpdateType::Update);
+        assert_eq!(payload.id, "b");
+    }
+
+    #[test]
+    fn test_batch_reports_earliest_publish_time() {
+        let mut batch = Batch::new(CoalescingOptions::new(Duration::from_secs(1)));
+        let now = SystemTime::now();
+        let earlier = now - Duration::from_millis(100);
+        for published_at in [None, Some(now), Some(earlier)] {
+            batch.push(
+                received(UpdateType::UpdateForAddPolicy, "a"),
+                Instant::now(),
+                published_at,
+            );
+        }
+        let (_, _, published_at) = batch.take().unwrap();
+        assert_eq!(published_at, Some(earlier));
+
+        batch.push(
+            received(UpdateType::UpdateForAddPolicy, "a"),
+            Instant::now(),
+            None,
+        );
+        let (_, _, published_at) = batch.take().unwrap();
+        assert!(published_at.is_none());
+    }
+
+    #[test]
+    fn test_unbounded_window_never_closes() {
+        let mut batch = Batch::new(CoalescingOptions::new(Duration::MAX));
+        assert!(batch.is_empty());
+        batch.push(
+            received(UpdateType::UpdateForAddPolicy, "a"),
+            Instant::now(),
+            None,
+        );
+        assert!(!batch.is_empty());
+        assert!(batch.deadline().is_none());
+        assert!(batch.take().is_some());
+        assert!(batch.is_empty());
+    }
+
+    #[test]
+    fn test_merge_replaces_several_messages_by_update() {
+        assert!(merge(Vec::new()).is_none());
+
+        let (single, _) = merge(vec![received(UpdateType::UpdateForAddPolicy, "a")]).unwrap();
+        assert_eq!(single.method, UpdateType::UpdateForAddPolicy);
+
+        let (merged, payload) = merge(vec![
+            received(UpdateType::UpdateForAddPolicy, "a"),
+            received(UpdateType::UpdateForRemovePolicy, "b"),
+        ])
+        .unwrap();
+        assert_eq!(merged.method, UpdateType::Update);
+        assert_eq!(merged.id, "b");
+        assert_eq!(
+            Message::from_json(&payload).unwrap().method,
+            UpdateType::Update
+        );
+    }
+
+    #[test]
+    fn test_full_batch_is_reported() {
+        let options = CoalescingOptions::new(Duration::from_secs(1)).with_max_batch(2);
+        assert!(validate(&options).is_ok());
+        assert!(validate(&options.clone().with_max_batch(0)).is_err());
+
+        let mut batch = Batch::new(options);
+        assert!(!batch.push(
+            received(UpdateType::UpdateForAddPolicy, "a"),
+            Instant::now(),
+            None
+        ));
+        assert!(batch.push(
+            received(UpdateType::UpdateForAddPolicy, "b"),
+            Instant::now(),
+            None
+        ));
+    }
+}
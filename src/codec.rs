@@ -0,0 +1,237 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Serialization formats for messages on the wire.
+//!
+//! JSON payloads are sent as-is, so they stay compatible with the Go watcher.
+//! Payloads of any other codec are prefixed with their content type:
+//!
+//! ```text
+//! \0<content type>\0<encoded envelope>
+//! ```
+//!
+//! Receivers pick the codec from the marker, so instances using different
+//! codecs can share a channel while a fleet migrates.
+
+use crate::watcher::Envelope;
+use crate::{Result, WatcherError};
+use std::fmt::Debug;
+
+/// Delimits the content type marker; JSON payloads never start with it
+const MARKER: u8 = 0;
+
+/// Serialization format of published messages
+pub trait Codec: Debug + Send + Sync {
+    /// MIME type identifying payloads produced by this codec
+    fn content_type(&self) -> &str;
+
+    /// Serialize `envelope`
+    ///
+    /// An envelope with version 0 stands for a bare message, which codecs may
+    /// encode without the envelope fields.
+    fn encode(&self, envelope: &Envelope) -> Result<Vec<u8>>;
+
+    /// Deserialize data produced by [`Codec::encode`]
+    fn decode(&self, data: &[u8]) -> Result<Envelope>;
+}
+
+/// JSON codec, the default
+///
+/// Bare messages are written as plain [`Message`](crate::Message) JSON.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+impl JsonCodec {
+    pub const CONTENT_TYPE: &'static str = "application/json";
+}
+
+impl Codec for JsonCodec {
+    fn content_type(&self) -> &str {
+        Self::CONTENT_TYPE
+    }
+
+    fn encode(&self, envelope: &Envelope) -> Result<Vec<u8>> {
+        if envelope.version == 0 {
+            Ok(serde_json::to_vec(&envelope.payload)?)
+        } else {
+            Ok(serde_json::to_vec(envelope)?)
+        }
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Envelope> {
+        let json = std::str::from_utf8(data)
+            .map_err(|e| WatcherError::Codec(format!("payload is not UTF-8: {}", e)))?;
+        Envelope::decode(json)
+    }
+}
+
+/// MessagePack codec, with field names kept so fields can be added safely
+#[cfg(feature = "msgpack")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MessagePackCodec;
+
+#[cfg(feature = "msgpack")]
+impl MessagePackCodec {
+    pub const CONTENT_TYPE: &'static str = "application/msgpack";
+}
+
+#[cfg(feature = "msgpack")]
+impl Codec for MessagePackCodec {
+    fn content_type(&self) -> &str {
+        Self::CONTENT_TYPE
+    }
+
+    fn encode(&self, envelope: &Envelope) -> Result<Vec<u8>> {
+        rmp_serde::to_vec_named(envelope).map_err(|e| WatcherError::Codec(e.to_string()))
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Envelope> {
+        rmp_serde::from_slice(data).map_err(|e| WatcherError::Codec(e.to_string()))
+    }
+}
+
+/// CBOR codec
+#[cfg(feature = "cbor")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CborCodec;
+
+#[cfg(feature = "cbor")]
+impl CborCodec {
+    pub const CONTENT_TYPE: &'static str = "application/cbor";
+}
+
+#[cfg(feature = "cbor")]
+impl Codec for CborCodec {
+    fn content_type(&self) -> &str {
+        Self::CONTENT_TYPE
+    }
+
+    fn encode(&self, envelope: &Envelope) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        ciborium::into_writer(envelope, &mut data)
+            .map_err(|e| WatcherError::Codec(e.to_string()))?;
+        Ok(data)
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Envelope> {
+        ciborium::from_reader(data).map_err(|e| WatcherError::Codec(e.to_string()))
+    }
+}
+
+/// Encode `envelope` with `codec`, marking the content type unless it is JSON
+pub(crate) fn encode(codec: &dyn Codec, envelope: &Envelope) -> Result<Vec<u8>> {
+    let body = codec.encode(envelope)?;
+    let content_type = codec.content_type();
+    if content_type == JsonCodec::CONTENT_TYPE {
+        return Ok(body);
+    }
+
+    let mut data = Vec::with_capacity(content_type.len() + body.len() + 2);
+    data.push(MARKER);
+    data.extend_from_slice(content_type.as_bytes());
+    data.push(MARKER);
+    data.extend_from_slice(&body);
+    Ok(data)
+}
+
+/// Whether `data` is unmarked, i.e. JSON
+pub(crate) fn is_json(data: &[u8]) -> bool {
+    data.first() != Some(&MARKER)
+}
+
+/// Decode `data` with the codec named by its marker
+///
+/// Besides JSON and `configured`, every codec compiled into the crate is
+/// recognized.
+pub(crate) fn decode(configured: &dyn Codec, data: &[u8]) -> Result<Envelope> {
+    let Some(rest) = data.strip_prefix(&[MARKER]) else {
+        return JsonCodec.decode(data);
+    };
+    let end = rest
+        .iter()
+        .position(|b| *b == MARKER)
+        .ok_or_else(|| WatcherError::Codec("malformed content type marker".to_string()))?;
+    let content_type = String::from_utf8_lossy(&rest[..end]);
+    let body = &rest[end + 1..];
+
+    match content_type.as_ref() {
+        content_type if content_type == configured.content_type() => configured.decode(body),
+        JsonCodec::CONTENT_TYPE => JsonCodec.decode(body),
+        #[cfg(feature = "msgpack")]
+        MessagePackCodec::CONTENT_TYPE => MessagePackCodec.decode(body),
+        #[cfg(feature = "cbor")]
+        CborCodec::CONTENT_TYPE => CborCodec.decode(body),
+        content_type => Err(WatcherError::Codec(format!(
+            "unsupported content type {:?}",
+            content_type
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Message, UpdateType};
+
+    fn envelope() -> Envelope {
+        let mut message = Message::new(UpdateType::UpdateForAddPolicies, "node-1".to_string());
+        message.sec = "p".to_string();
+        message.ptype = "p".to_string();
+        message.new_rules = vec![vec!["alice".to_string(), "data1".to_string()]];
+        Envelope::new(message)
+    }
+
+    fn roundtrip(codec: &dyn Codec) {
+        let data = encode(codec, &envelope()).unwrap();
+        let decoded = decode(&JsonCodec, &data).unwrap();
+        assert_eq!(decoded.version, Envelope::VERSION);
+        assert_eq!(decoded.sender, "node-1");
+        assert_eq!(decoded.payload.method, UpdateType::UpdateForAddPolicies);
+        assert_eq!(decoded.payload.new_rules, envelope().payload.new_rules);
+    }
+
+    #[test]
+    fn test_json_is_unmarked() {
+        let mut bare = envelope();
+        bare.version = 0;
+        let data = encode(&JsonCodec, &bare).unwrap();
+        assert!(data.starts_with(b"{\"Method\":\"UpdateForAddPolicies\""));
+        assert!(is_json(&data));
+        roundtrip(&JsonCodec);
+    }
+
+    #[test]
+    fn test_unknown_content_type_is_rejected() {
+        assert!(matches!(
+            decode(&JsonCodec, b"\0application/x-unknown\0data"),
+            Err(WatcherError::Codec(_))
+        ));
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn test_msgpack_roundtrip() {
+        let data = encode(&MessagePackCodec, &envelope()).unwrap();
+        assert!(data.starts_with(b"\0application/msgpack\0"));
+        roundtrip(&MessagePackCodec);
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn test_cbor_roundtrip() {
+        let data = encode(&CborCodec, &envelope()).unwrap();
+        assert!(data.starts_with(b"\0application/cbor\0"));
+        roundtrip(&CborCodec);
+    }
+}
@@ -0,0 +1,205 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compression of large payloads.
+//!
+//! Compressed payloads are standard zstd, gzip or LZ4 frames. Receivers
+//! recognize them by the magic number every frame starts with, which neither
+//! JSON nor codec markers can start with, so no extra header is needed.
+
+use crate::options::{CompressionAlgorithm, CompressionOptions};
+use crate::{Result, WatcherError};
+use std::io::Read;
+
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xB5, 0x2F, 0xFD];
+const GZIP_MAGIC: &[u8] = &[0x1F, 0x8B];
+const LZ4_MAGIC: &[u8] = &[0x04, 0x22, 0x4D, 0x18];
+
+/// Decompressed payloads larger than this are rejected
+#[cfg_attr(
+    not(any(feature = "zstd", feature = "gzip", feature = "lz4")),
+    allow(dead_code)
+)]
+const MAX_DECOMPRESSED_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Check that the configured algorithm is compiled into the crate
+pub(crate) fn validate(compression: &CompressionOptions) -> Result<()> {
+    let enabled = match compression.algorithm {
+        CompressionAlgorithm::Zstd => cfg!(feature = "zstd"),
+        CompressionAlgorithm::Gzip => cfg!(feature = "gzip"),
+        CompressionAlgorithm::Lz4 => cfg!(feature = "lz4"),
+    };
+    if !enabled {
+        return Err(WatcherError::Configuration(format!(
+            "{0} compression requires the `{0}` feature",
+            compression.algorithm.name()
+        )));
+    }
+    Ok(())
+}
+
+/// Compress `payload` if it reaches the threshold
+///
+/// Returns `None` when the payload is too small or compression doesn't make
+/// it any smaller.
+pub(crate) fn compress(
+    compression: &CompressionOptions,
+    payload: &[u8],
+) -> Result<Option<Vec<u8>>> {
+    if payload.len() < compression.threshold {
+        return Ok(None);
+    }
+
+    let compressed = encode(compression.algorithm, payload)?;
+    Ok((compressed.len() < payload.len()).then_some(compressed))
+}
+
+#[cfg_attr(
+    not(any(feature = "zstd", feature = "gzip", feature = "lz4")),
+    allow(unused_variables)
+)]
+fn encode(algorithm: CompressionAlgorithm, payload: &[u8]) -> Result<Vec<u8>> {
+    match algorithm {
+        #[cfg(feature = "zstd")]
+        CompressionAlgorithm::Zstd => zstd::bulk::compress(payload, 0).map_err(failed),
+        #[cfg(feature = "gzip")]
+        CompressionAlgorithm::Gzip => {
+            use std::io::Write;
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(payload).map_err(failed)?;
+            encoder.finish().map_err(failed)
+        }
+        #[cfg(feature = "lz4")]
+        CompressionAlgorithm::Lz4 => {
+            use std::io::Write;
+            let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+            encoder.write_all(payload).map_err(failed)?;
+            encoder.finish().map_err(failed)
+        }
+        #[allow(unreachable_patterns)]
+        algorithm => Err(unsupported(algorithm.name())),
+    }
+}
+
+/// Decompress `data` if it is a compressed frame, or return it as-is
+///
+/// Compressed payloads are recognized whether or not compression is enabled
+/// locally, but only algorithms compiled into the crate can be decompressed.
+pub(crate) fn decompress(data: Vec<u8>) -> Result<Vec<u8>> {
+    if data.starts_with(ZSTD_MAGIC) {
+        #[cfg(feature = "zstd")]
+        return read_bounded(zstd::stream::read::Decoder::new(data.as_slice()).map_err(failed)?);
+        #[cfg(not(feature = "zstd"))]
+        return Err(unsupported("zstd"));
+    }
+    if data.starts_with(GZIP_MAGIC) {
+        #[cfg(feature = "gzip")]
+        return read_bounded(flate2::read::GzDecoder::new(data.as_slice()));
+        #[cfg(not(feature = "gzip"))]
+        return Err(unsupported("gzip"));
+    }
+    if data.starts_with(LZ4_MAGIC) {
+        #[cfg(feature = "lz4")]
+        return read_bounded(lz4_flex::frame::FrameDecoder::new(data.as_slice()));
+        #[cfg(not(feature = "lz4"))]
+        return Err(unsupported("lz4"));
+    }
+    Ok(data)
+}
+
+/// Read a decompressed stream, guarding against decompression bombs
+#[cfg_attr(
+    not(any(feature = "zstd", feature = "gzip", feature = "lz4")),
+    allow(dead_code)
+)]
+fn read_bounded(reader: impl Read) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    reader
+        .take(MAX_DECOMPRESSED_SIZE + 1)
+        .read_to_end(&mut data)
+        .map_err(failed)?;
+    if data.len() as u64 > MAX_DECOMPRESSED_SIZE {
+        return Err(WatcherError::Compression(format!(
+            "decompressed payload exceeds {} bytes",
+            MAX_DECOMPRESSED_SIZE
+        )));
+    }
+    Ok(data)
+}
+
+#[cfg_attr(
+    not(any(feature = "zstd", feature = "gzip", feature = "lz4")),
+    allow(dead_code)
+)]
+fn failed(e: impl std::fmt::Display) -> WatcherError {
+    WatcherError::Compression(e.to_string())
+}
+
+#[cfg_attr(
+    all(feature = "zstd", feature = "gzip", feature = "lz4"),
+    allow(dead_code)
+)]
+fn unsupported(feature: &str) -> WatcherError {
+    WatcherError::Compression(format!(
+        "{0} compression requires the `{0}` feature",
+        feature
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uncompressed_payload_passes_through() {
+        let json = br#"{"Method":"UpdateForSavePolicy","ID":"node-1"}"#.to_vec();
+        assert_eq!(decompress(json.clone()).unwrap(), json);
+    }
+
+    #[cfg(any(feature = "zstd", feature = "gzip", feature = "lz4"))]
+    fn algorithms() -> Vec<CompressionAlgorithm> {
+        vec![
+            #[cfg(feature = "zstd")]
+            CompressionAlgorithm::Zstd,
+            #[cfg(feature = "gzip")]
+            CompressionAlgorithm::Gzip,
+            #[cfg(feature = "lz4")]
+            CompressionAlgorithm::Lz4,
+        ]
+    }
+
+    #[cfg(any(feature = "zstd", feature = "gzip", feature = "lz4"))]
+    #[test]
+    fn test_compress_and_decompress_roundtrip() {
+        let payload = br#"["alice","data1","read"],"#.repeat(1000);
+        for algorithm in algorithms() {
+            let options = CompressionOptions::new(algorithm);
+            let compressed = compress(&options, &payload).unwrap().unwrap();
+            assert!(compressed.len() < payload.len() / 10, "{:?}", algorithm);
+            assert_eq!(decompress(compressed).unwrap(), payload);
+        }
+    }
+
+    #[cfg(any(feature = "zstd", feature = "gzip", feature = "lz4"))]
+    #[test]
+    fn test_small_payloads_are_not_compressed() {
+        for algorithm in algorithms() {
+            let options = CompressionOptions::new(algorithm).with_threshold(1024);
+            assert!(compress(&options, b"{\"Method\":\"Update\"}")
+                .unwrap()
+                .is_none());
+        }
+    }
+}
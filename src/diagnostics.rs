@@ -0,0 +1,193 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Logging shims routing diagnostics to `log`, or to `tracing` with the
+//! `tracing` feature.
+//!
+//! Spans are only recorded with `tracing`; without it they compile to no-ops.
+
+use crate::options::PayloadLogging;
+use crate::Message;
+use std::fmt;
+
+macro_rules! trace {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        tracing::trace!($($arg)+);
+        #[cfg(not(feature = "tracing"))]
+        log::trace!($($arg)+);
+    }};
+}
+
+macro_rules! debug {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)+);
+        #[cfg(not(feature = "tracing"))]
+        log::debug!($($arg)+);
+    }};
+}
+
+macro_rules! info {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        tracing::info!($($arg)+);
+        #[cfg(not(feature = "tracing"))]
+        log::info!($($arg)+);
+    }};
+}
+
+macro_rules! log_warn {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        tracing::warn!($($arg)+);
+        #[cfg(not(feature = "tracing"))]
+        log::warn!($($arg)+);
+    }};
+}
+
+macro_rules! error {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        tracing::error!($($arg)+);
+        #[cfg(not(feature = "tracing"))]
+        log::error!($($arg)+);
+    }};
+}
+
+/// Create a debug-level span, or a no-op [`Span`] without `tracing`
+macro_rules! span {
+    ($name:literal $(, $($field:tt)+)?) => {{
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!($name $(, $($field)+)?);
+        #[cfg(not(feature = "tracing"))]
+        let span = $crate::diagnostics::Span;
+        span
+    }};
+}
+
+// `warn` clashes with the built-in attribute of the same name when exported directly
+pub(crate) use {debug, error, info, log_warn as warn, span, trace};
+
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::{Instrument, Span};
+
+/// Stand-in for `tracing::Span` when the `tracing` feature is disabled
+#[cfg(not(feature = "tracing"))]
+#[derive(Clone)]
+pub(crate) struct Span;
+
+#[cfg(not(feature = "tracing"))]
+impl Span {
+    pub(crate) fn in_scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        f()
+    }
+}
+
+/// Stand-in for `tracing::Instrument` when the `tracing` feature is disabled
+#[cfg(not(feature = "tracing"))]
+pub(crate) trait Instrument: Sized {
+    fn instrument(self, _span: Span) -> Self {
+        self
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+impl<F: std::future::Future> Instrument for F {}
+
+/// A payload rendered for logs according to [`PayloadLogging`]
+pub(crate) struct Payload<'a> {
+    mode: PayloadLogging,
+    raw: &'a [u8],
+    message: Option<&'a Message>,
+}
+
+impl<'a> Payload<'a> {
+    pub(crate) fn new(mode: PayloadLogging, raw: &'a [u8], message: Option<&'a Message>) -> Self {
+        Self { mode, raw, message }
+    }
+}
+
+impl fmt::Display for Payload<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.mode, self.message) {
+            (PayloadLogging::Full, _) if std::str::from_utf8(self.raw).is_ok() => {
+                f.write_str(&String::from_utf8_lossy(self.raw))
+            }
+            // Binary codecs are logged as the JSON equivalent of the message
+            (PayloadLogging::Full, Some(message)) => match message.to_json() {
+                Ok(json) => f.write_str(&json),
+                Err(_) => write!(f, "<{} bytes>", self.raw.len()),
+            },
+            (PayloadLogging::Redacted, Some(message)) => write!(
+                f,
+                "{{method: {}, id: {}, sec: {}, ptype: {}, rules: {}}}",
+                message.method,
+                message.id,
+                message.sec,
+                message.ptype,
+                rule_count(message)
+            ),
+            _ => write!(f, "<{} bytes>", self.raw.len()),
+        }
+    }
+}
+
+/// Number of rules carried by `message`, without revealing them
+fn rule_count(message: &Message) -> usize {
+    usize::from(!message.old_rule.is_empty())
+        + usize::from(!message.new_rule.is_empty())
+        + message.old_rules.len()
+        + message.new_rules.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UpdateType;
+
+    fn message() -> (Message, String) {
+        let mut message = Message::new(UpdateType::UpdateForAddPolicy, "node-1".to_string());
+        message.sec = "p".to_string();
+        message.ptype = "p".to_string();
+        message.new_rule = vec!["alice".to_string(), "data1".to_string(), "read".to_string()];
+        let json = message.to_json().unwrap();
+        (message, json)
+    }
+
+    #[test]
+    fn test_payload_logging_modes() {
+        let (message, json) = message();
+
+        let off = Payload::new(PayloadLogging::Off, json.as_bytes(), Some(&message)).to_string();
+        assert_eq!(off, format!("<{} bytes>", json.len()));
+
+        let redacted =
+            Payload::new(PayloadLogging::Redacted, json.as_bytes(), Some(&message)).to_string();
+        assert_eq!(
+            redacted,
+            "{method: UpdateForAddPolicy, id: node-1, sec: p, ptype: p, rules: 1}"
+        );
+        assert!(!redacted.contains("alice"));
+
+        let full = Payload::new(PayloadLogging::Full, json.as_bytes(), Some(&message)).to_string();
+        assert_eq!(full, json);
+    }
+
+    #[test]
+    fn test_redacted_unparsed_payload_hides_content() {
+        let redacted = Payload::new(PayloadLogging::Redacted, b"not json", None).to_string();
+        assert_eq!(redacted, "<8 bytes>");
+    }
+}
@@ -0,0 +1,238 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ChaCha20-Poly1305 encryption of payloads.
+//!
+//! An encrypted payload is a JSON envelope keeping the sender ID readable,
+//! so messages can still be routed or filtered without decrypting them:
+//!
+//! ```text
+//! {"ID":"<sender>","Alg":"ChaCha20-Poly1305","KeyId":"<key id>","Nonce":"<base64>","Ciphertext":"<base64>"}
+//! ```
+//!
+//! The sender ID is authenticated as associated data, so it cannot be
+//! changed without failing decryption.
+
+use crate::options::EncryptionOptions;
+use crate::{Result, WatcherError};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const ALGORITHM: &str = "ChaCha20-Poly1305";
+
+/// 256-bit ChaCha20-Poly1305 key
+pub type EncryptionKey = [u8; 32];
+
+/// Source of the keys used to encrypt and decrypt messages
+///
+/// Implement this to fetch keys from a secret store or KMS. All instances
+/// sharing a channel must resolve the same key IDs to the same keys.
+pub trait KeyProvider: Send + Sync {
+    /// Key used to encrypt published messages, along with its ID
+    fn current_key(&self) -> Result<(String, EncryptionKey)>;
+
+    /// Key registered under `key_id`, used to decrypt received messages
+    fn key(&self, key_id: &str) -> Result<Option<EncryptionKey>>;
+}
+
+/// [`KeyProvider`] holding a fixed set of keys
+///
+/// Messages are encrypted with the key passed to [`StaticKeyProvider::new`];
+/// keys added with [`StaticKeyProvider::with_key`] are only used to decrypt,
+/// which allows rotating keys across a fleet.
+#[derive(Clone)]
+pub struct StaticKeyProvider {
+    current: String,
+    keys: HashMap<String, EncryptionKey>,
+}
+
+impl StaticKeyProvider {
+    /// Encrypt with `key`, identified by `key_id`
+    pub fn new(key_id: String, key: EncryptionKey) -> Self {
+        Self {
+            keys: HashMap::from([(key_id.clone(), key)]),
+            current: key_id,
+        }
+    }
+
+    /// Also decrypt messages encrypted with `key`, identified by `key_id`
+    pub fn with_key(mut self, key_id: String, key: EncryptionKey) -> Self {
+        self.keys.entry(key_id).or_insert(key);
+        self
+    }
+}
+
+impl KeyProvider for StaticKeyProvider {
+    fn current_key(&self) -> Result<(String, EncryptionKey)> {
+        Ok((self.current.clone(), self.keys[&self.current]))
+    }
+
+    fn key(&self, key_id: &str) -> Result<Option<EncryptionKey>> {
+        Ok(self.keys.get(key_id).copied())
+    }
+}
+
+/// Envelope carrying an encrypted payload
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Envelope {
+    #[serde(rename = "ID")]
+    id: String,
+    alg: String,
+    key_id: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Encrypt `payload` sent by `sender_id` into an envelope
+pub(crate) fn encrypt(
+    encryption: &EncryptionOptions,
+    sender_id: &str,
+    payload: &[u8],
+) -> Result<Vec<u8>> {
+    let (key_id, key) = encryption.provider.current_key()?;
+    let nonce: [u8; 12] = rand::random();
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&key))
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: payload,
+                aad: sender_id.as_bytes(),
+            },
+        )
+        .map_err(|_| WatcherError::Encryption("failed to encrypt payload".to_string()))?;
+
+    let envelope = Envelope {
+        id: sender_id.to_string(),
+        alg: ALGORITHM.to_string(),
+        key_id,
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    };
+    Ok(serde_json::to_vec(&envelope)?)
+}
+
+/// Decrypt an envelope produced by [`encrypt`]
+///
+/// Payloads that are not an envelope are returned as-is when plaintext is
+/// accepted.
+pub(crate) fn decrypt(encryption: &EncryptionOptions, data: &[u8]) -> Result<Vec<u8>> {
+    let Ok(envelope) = serde_json::from_slice::<Envelope>(data) else {
+        if encryption.accept_plaintext {
+            return Ok(data.to_vec());
+        }
+        return Err(invalid("message is not encrypted"));
+    };
+    if envelope.alg != ALGORITHM {
+        return Err(invalid(&format!(
+            "unsupported algorithm {:?}",
+            envelope.alg
+        )));
+    }
+
+    let key = encryption
+        .provider
+        .key(&envelope.key_id)?
+        .ok_or_else(|| invalid(&format!("unknown key ID {:?}", envelope.key_id)))?;
+    let nonce = BASE64
+        .decode(&envelope.nonce)
+        .ok()
+        .filter(|nonce| nonce.len() == 12)
+        .ok_or_else(|| invalid("malformed nonce"))?;
+    let ciphertext = BASE64
+        .decode(&envelope.ciphertext)
+        .map_err(|_| invalid("malformed ciphertext"))?;
+
+    ChaCha20Poly1305::new(Key::from_slice(&key))
+        .decrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &ciphertext,
+                aad: envelope.id.as_bytes(),
+            },
+        )
+        .map_err(|_| invalid(&format!("cannot decrypt message from {}", envelope.id)))
+}
+
+fn invalid(reason: &str) -> WatcherError {
+    WatcherError::Encryption(reason.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn encryption(provider: StaticKeyProvider) -> EncryptionOptions {
+        EncryptionOptions::new(Arc::new(provider))
+    }
+
+    #[test]
+    fn test_encrypt_and_decrypt_roundtrip() {
+        let options = encryption(StaticKeyProvider::new("k1".to_string(), [7; 32]));
+        let plaintext = br#"{"Method":"UpdateForAddPolicy","ID":"node-1","NewRule":["alice"]}"#;
+
+        let encrypted = encrypt(&options, "node-1", plaintext).unwrap();
+        let envelope: serde_json::Value = serde_json::from_slice(&encrypted).unwrap();
+        assert_eq!(envelope["ID"], "node-1");
+        assert_eq!(envelope["KeyId"], "k1");
+        assert!(!String::from_utf8_lossy(&encrypted).contains("alice"));
+
+        assert_eq!(decrypt(&options, &encrypted).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn test_decrypt_rejects_changed_sender() {
+        let options = encryption(StaticKeyProvider::new("k1".to_string(), [7; 32]));
+        let encrypted = encrypt(&options, "node-1", b"payload").unwrap();
+
+        let mut envelope: serde_json::Value = serde_json::from_slice(&encrypted).unwrap();
+        envelope["ID"] = "node-2".into();
+        let forged = serde_json::to_vec(&envelope).unwrap();
+        assert!(matches!(
+            decrypt(&options, &forged),
+            Err(WatcherError::Encryption(_))
+        ));
+    }
+
+    #[test]
+    fn test_decrypt_with_rotated_key() {
+        let old = encryption(StaticKeyProvider::new("k0".to_string(), [1; 32]));
+        let encrypted = encrypt(&old, "node-1", b"payload").unwrap();
+
+        let new = encryption(StaticKeyProvider::new("k1".to_string(), [2; 32]));
+        assert!(decrypt(&new, &encrypted).is_err());
+
+        let rotated = encryption(
+            StaticKeyProvider::new("k1".to_string(), [2; 32]).with_key("k0".to_string(), [1; 32]),
+        );
+        assert_eq!(decrypt(&rotated, &encrypted).unwrap(), b"payload");
+    }
+
+    #[test]
+    fn test_decrypt_plaintext() {
+        let options = encryption(StaticKeyProvider::new("k1".to_string(), [7; 32]));
+        assert!(decrypt(&options, b"{\"Method\":\"Update\"}").is_err());
+
+        let lenient = options.with_accept_plaintext(true);
+        assert_eq!(
+            decrypt(&lenient, b"{\"Method\":\"Update\"}").unwrap(),
+            b"{\"Method\":\"Update\"}"
+        );
+    }
+}
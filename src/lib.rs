@@ -70,15 +70,59 @@
 //!     Ok(())
 //! }
 //! ```
+//!
+//! ## Custom transport
+//!
+//! [`RedisWatcher`] is generic over a [`Transport`], so any publish/subscribe
+//! backend can be plugged in with [`RedisWatcher::with_transport`]:
+//!
+//! ```rust,no_run
+//! use redis_watcher::{RedisTransport, RedisWatcher, WatcherOptions};
+//!
+//! fn main() -> redis_watcher::Result<()> {
+//!     let transport = RedisTransport::standalone("redis://127.0.0.1:6379")?;
+//!     let watcher = RedisWatcher::with_transport(transport, WatcherOptions::default())?;
+//!     # drop(watcher);
+//!     Ok(())
+//! }
+//! ```
 
+mod batching;
+mod chunking;
+mod coalescing;
+mod codec;
+mod compression;
+mod diagnostics;
+mod encryption;
+mod metrics;
 mod options;
+mod policy;
+mod queue;
+mod replay;
+mod signing;
+pub mod transport;
 mod watcher;
 
 #[cfg(test)]
 mod watcher_test;
 
-pub use options::WatcherOptions;
+#[cfg(feature = "cbor")]
+pub use codec::CborCodec;
+#[cfg(feature = "msgpack")]
+pub use codec::MessagePackCodec;
+pub use codec::{Codec, JsonCodec};
+pub use encryption::{EncryptionKey, KeyProvider, StaticKeyProvider};
+pub use metrics::{LatencyHistogram, MetricsSnapshot};
+pub use options::{
+    BatchingOptions, ChunkingOptions, CoalescingOptions, CompressionAlgorithm, CompressionOptions,
+    DeliveryMode, EncryptionOptions, HistoryOptions, MessageFormat, OverflowPolicy, PayloadLogging,
+    PublishQueueOptions, ReconnectOptions, SigningOptions, StreamOptions, WatcherOptions,
+};
+pub use policy::apply_message;
+pub use transport::{MemoryTransport, RedisTransport, Transport};
 pub use watcher::RedisWatcher;
 
 /// Re-export for convenience
-pub use watcher::{Message, Result, UpdateType, WatcherError};
+pub use watcher::{
+    Envelope, Message, PublishReceipt, Result, UpdateType, WatcherError, WatcherEvent,
+};
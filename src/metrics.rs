@@ -0,0 +1,402 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Counters and latency histograms describing watcher activity.
+//!
+//! Values are always kept in memory and exposed through
+//! [`RedisWatcher::metrics`](crate::RedisWatcher::metrics). With the `metrics`
+//! feature, they are also reported through the `metrics` crate facade,
+//! labelled with the channel name.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Upper bounds of the latency histogram buckets
+const LATENCY_BUCKETS: [Duration; 10] = [
+    Duration::from_millis(1),
+    Duration::from_millis(5),
+    Duration::from_millis(10),
+    Duration::from_millis(25),
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_secs(5),
+];
+
+/// Point-in-time copy of the watcher metrics
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MetricsSnapshot {
+    /// Messages successfully published
+    pub published: u64,
+    /// Publish attempts that failed and were retried
+    pub publish_retries: u64,
+    /// Messages given up on after all publish attempts failed
+    pub publish_failures: u64,
+    /// Messages received on the channel, including ignored ones
+    ///
+    /// A chunked message counts once, when its last chunk arrives, and each
+    /// message of a batch counts separately.
+    pub received: u64,
+    /// Received messages ignored because this instance sent them
+    pub ignored_self: u64,
+    /// Received payloads that could not be parsed into a [`Message`](crate::Message)
+    pub parse_errors: u64,
+    /// Received payloads rejected because their signature is missing or invalid
+    pub invalid_signatures: u64,
+    /// Received payloads rejected because they could not be decrypted
+    pub decryption_failures: u64,
+    /// Received messages handed to at least one callback
+    ///
+    /// A burst of messages delivered together by coalescing counts once.
+    pub callback_invocations: u64,
+    /// Times the subscription was re-established after being lost
+    pub reconnects: u64,
+    /// Times messages from a sender were found missing from its sequence numbers
+    pub sequence_gaps: u64,
+    /// Signed messages replaced by a full reload because they were not newer
+    /// than the last one from their sender
+    pub stale_messages: u64,
+    /// Chunked messages discarded because not all chunks arrived in time
+    pub incomplete_transfers: u64,
+    /// Chunked messages discarded to stay within the reassembly limits
+    pub evicted_transfers: u64,
+    /// Published messages that were compressed
+    pub compressed: u64,
+    /// Size of compressed messages before compression, in bytes
+    pub uncompressed_bytes: u64,
+    /// Size of compressed messages after compression, in bytes
+    pub compressed_bytes: u64,
+    /// Batches of several messages published as one payload
+    pub batches: u64,
+    /// Messages replayed from the history after (re)subscribing
+    pub replayed: u64,
+    /// Messages dropped because they were already received live or replayed
+    pub duplicates: u64,
+    /// Messages published while the publish queue was full
+    pub queue_overflows: u64,
+    /// Time from queueing a message until the transport acknowledged it
+    pub publish_latency: LatencyHistogram,
+    /// Time from receiving a message until its callbacks returned
+    pub callback_latency: LatencyHistogram,
+    /// Time from publishing a message until its callbacks returned
+    ///
+    /// Only recorded for messages published with
+    /// [`MessageFormat::Envelope`](crate::MessageFormat::Envelope), which
+    /// carry their publish time, so it includes the clock difference between
+    /// the instances. A burst delivered together by coalescing is measured
+    /// from its earliest message.
+    pub delivery_latency: LatencyHistogram,
+}
+
+impl MetricsSnapshot {
+    /// Overall ratio of uncompressed to compressed size of compressed messages
+    ///
+    /// `None` until a message has been compressed.
+    pub fn compression_ratio(&self) -> Option<f64> {
+        (self.compressed_bytes > 0)
+            .then(|| self.uncompressed_bytes as f64 / self.compressed_bytes as f64)
+    }
+}
+
+/// Cumulative latency histogram
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    /// Upper bound of each bucket with the number of samples at or below it
+    ///
+    /// Samples above the last bound are only included in `count`.
+    pub buckets: Vec<(Duration, u64)>,
+    /// Number of samples
+    pub count: u64,
+    /// Sum of all samples
+    pub sum: Duration,
+}
+
+/// Lock-free latency histogram
+#[derive(Default)]
+struct Histogram {
+    buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl Histogram {
+    fn record(&self, latency: Duration) {
+        if let Some(index) = LATENCY_BUCKETS.iter().position(|bound| latency <= *bound) {
+            self.buckets[index].fetch_add(1, Ordering::Relaxed);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        self.sum_micros.fetch_add(micros, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> LatencyHistogram {
+        let mut cumulative = 0;
+        let buckets = LATENCY_BUCKETS
+            .iter()
+            .zip(&self.buckets)
+            .map(|(bound, samples)| {
+                cumulative += samples.load(Ordering::Relaxed);
+                (*bound, cumulative)
+            })
+            .collect();
+        LatencyHistogram {
+            buckets,
+            count: self.count.load(Ordering::Relaxed),
+            sum: Duration::from_micros(self.sum_micros.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// Metrics shared between the watcher and its background tasks
+#[derive(Default)]
+pub(crate) struct Metrics {
+    #[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+    channel: String,
+    published: AtomicU64,
+    publish_retries: AtomicU64,
+    publish_failures: AtomicU64,
+    received: AtomicU64,
+    ignored_self: AtomicU64,
+    parse_errors: AtomicU64,
+    invalid_signatures: AtomicU64,
+    decryption_failures: AtomicU64,
+    callback_invocations: AtomicU64,
+    reconnects: AtomicU64,
+    sequence_gaps: AtomicU64,
+    stale_messages: AtomicU64,
+    incomplete_transfers: AtomicU64,
+    evicted_transfers: AtomicU64,
+    compressed: AtomicU64,
+    uncompressed_bytes: AtomicU64,
+    compressed_bytes: AtomicU64,
+    batches: AtomicU64,
+    replayed: AtomicU64,
+    duplicates: AtomicU64,
+    queue_overflows: AtomicU64,
+    publish_latency: Histogram,
+    callback_latency: Histogram,
+    delivery_latency: Histogram,
+}
+
+/// Increment `counter` and its facade counter `name`
+macro_rules! count {
+    ($self:ident, $counter:ident, $name:literal) => {{
+        $self.$counter.fetch_add(1, Ordering::Relaxed);
+        #[cfg(feature = "metrics")]
+        ::metrics::counter!($name, "channel" => $self.channel.clone()).increment(1);
+    }};
+}
+
+/// Record `latency` in `histogram` and its facade histogram `name`
+macro_rules! observe {
+    ($self:ident, $histogram:ident, $name:literal, $latency:expr) => {{
+        let latency = $latency;
+        $self.$histogram.record(latency);
+        #[cfg(feature = "metrics")]
+        ::metrics::histogram!($name, "channel" => $self.channel.clone())
+            .record(latency.as_secs_f64());
+    }};
+}
+
+impl Metrics {
+    pub(crate) fn new(channel: &str) -> Self {
+        Self {
+            channel: channel.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn published(&self, latency: Duration) {
+        count!(self, published, "redis_watcher_published_total");
+        observe!(
+            self,
+            publish_latency,
+            "redis_watcher_publish_latency_seconds",
+            latency
+        );
+    }
+
+    pub(crate) fn publish_retry(&self) {
+        count!(self, publish_retries, "redis_watcher_publish_retries_total");
+    }
+
+    pub(crate) fn publish_failure(&self) {
+        count!(
+            self,
+            publish_failures,
+            "redis_watcher_publish_failures_total"
+        );
+    }
+
+    pub(crate) fn received(&self) {
+        count!(self, received, "redis_watcher_received_total");
+    }
+
+    pub(crate) fn ignored_self(&self) {
+        count!(self, ignored_self, "redis_watcher_ignored_self_total");
+    }
+
+    pub(crate) fn parse_error(&self) {
+        count!(self, parse_errors, "redis_watcher_parse_errors_total");
+    }
+
+    pub(crate) fn invalid_signature(&self) {
+        count!(
+            self,
+            invalid_signatures,
+            "redis_watcher_invalid_signatures_total"
+        );
+    }
+
+    pub(crate) fn decryption_failure(&self) {
+        count!(
+            self,
+            decryption_failures,
+            "redis_watcher_decryption_failures_total"
+        );
+    }
+
+    pub(crate) fn callback_invoked(&self, latency: Duration) {
+        count!(
+            self,
+            callback_invocations,
+            "redis_watcher_callback_invocations_total"
+        );
+        observe!(
+            self,
+            callback_latency,
+            "redis_watcher_callback_latency_seconds",
+            latency
+        );
+    }
+
+    pub(crate) fn delivered(&self, latency: Duration) {
+        observe!(
+            self,
+            delivery_latency,
+            "redis_watcher_delivery_latency_seconds",
+            latency
+        );
+    }
+
+    pub(crate) fn reconnected(&self) {
+        count!(self, reconnects, "redis_watcher_reconnects_total");
+    }
+
+    pub(crate) fn sequence_gap(&self) {
+        count!(self, sequence_gaps, "redis_watcher_sequence_gaps_total");
+    }
+
+    pub(crate) fn stale_message(&self) {
+        count!(self, stale_messages, "redis_watcher_stale_messages_total");
+    }
+
+    pub(crate) fn incomplete_transfer(&self) {
+        count!(
+            self,
+            incomplete_transfers,
+            "redis_watcher_incomplete_transfers_total"
+        );
+    }
+
+    pub(crate) fn evicted_transfer(&self) {
+        count!(
+            self,
+            evicted_transfers,
+            "redis_watcher_evicted_transfers_total"
+        );
+    }
+
+    pub(crate) fn compressed(&self, uncompressed: usize, compressed: usize) {
+        count!(self, compressed, "redis_watcher_compressed_total");
+        self.uncompressed_bytes
+            .fetch_add(uncompressed as u64, Ordering::Relaxed);
+        self.compressed_bytes
+            .fetch_add(compressed as u64, Ordering::Relaxed);
+        #[cfg(feature = "metrics")]
+        ::metrics::histogram!("redis_watcher_compression_ratio", "channel" => self.channel.clone())
+            .record(uncompressed as f64 / compressed as f64);
+    }
+
+    pub(crate) fn batch(&self) {
+        count!(self, batches, "redis_watcher_batches_total");
+    }
+
+    pub(crate) fn replayed(&self) {
+        count!(self, replayed, "redis_watcher_replayed_total");
+    }
+
+    pub(crate) fn duplicate(&self) {
+        count!(self, duplicates, "redis_watcher_duplicates_total");
+    }
+
+    pub(crate) fn queue_overflow(&self) {
+        count!(self, queue_overflows, "redis_watcher_queue_overflows_total");
+    }
+
+    pub(crate) fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            published: self.published.load(Ordering::Relaxed),
+            publish_retries: self.publish_retries.load(Ordering::Relaxed),
+            publish_failures: self.publish_failures.load(Ordering::Relaxed),
+            received: self.received.load(Ordering::Relaxed),
+            ignored_self: self.ignored_self.load(Ordering::Relaxed),
+            parse_errors: self.parse_errors.load(Ordering::Relaxed),
+            invalid_signatures: self.invalid_signatures.load(Ordering::Relaxed),
+            decryption_failures: self.decryption_failures.load(Ordering::Relaxed),
+            callback_invocations: self.callback_invocations.load(Ordering::Relaxed),
+            reconnects: self.reconnects.load(Ordering::Relaxed),
+            sequence_gaps: self.sequence_gaps.load(Ordering::Relaxed),
+            stale_messages: self.stale_messages.load(Ordering::Relaxed),
+            incomplete_transfers: self.incomplete_transfers.load(Ordering::Relaxed),
+            evicted_transfers: self.evicted_transfers.load(Ordering::Relaxed),
+            compressed: self.compressed.load(Ordering::Relaxed),
+            uncompressed_bytes: self.uncompressed_bytes.load(Ordering::Relaxed),
+            compressed_bytes: self.compressed_bytes.load(Ordering::Relaxed),
+            batches: self.batches.load(Ordering::Relaxed),
+            replayed: self.replayed.load(Ordering::Relaxed),
+            duplicates: self.duplicates.load(Ordering::Relaxed),
+            queue_overflows: self.queue_overflows.load(Ordering::Relaxed),
+            publish_latency: self.publish_latency.snapshot(),
+            callback_latency: self.callback_latency.snapshot(),
+            delivery_latency: self.delivery_latency.snapshot(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let metrics = Metrics::new("/casbin");
+        metrics.published(Duration::from_micros(500));
+        metrics.published(Duration::from_millis(20));
+        metrics.published(Duration::from_secs(10));
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.published, 3);
+
+        let latency = snapshot.publish_latency;
+        assert_eq!(latency.count, 3);
+        assert_eq!(latency.sum, Duration::from_micros(10_020_500));
+        assert_eq!(latency.buckets[0], (Duration::from_millis(1), 1));
+        assert_eq!(latency.buckets[3], (Duration::from_millis(25), 2));
+        assert_eq!(latency.buckets[9], (Duration::from_secs(5), 2));
+    }
+}
@@ -12,8 +12,660 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::codec::{Codec, JsonCodec};
+use crate::encryption::KeyProvider;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 use uuid::Uuid;
 
+/// How policy updates are delivered through Redis
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum DeliveryMode {
+    /// Classic pub/sub (PUBLISH/SUBSCRIBE), fire-and-forget
+    ///
+    /// Instances that are disconnected while a policy changes miss the update.
+    #[default]
+    PubSub,
+
+    /// Redis Streams (XADD/XREAD) with durable, at-least-once delivery
+    ///
+    /// The channel name is used as the stream key. Each instance tracks the
+    /// last entry it has seen and resumes from it after a reconnect.
+    Streams(StreamOptions),
+}
+
+/// Wire format of published messages
+///
+/// Received messages are decoded in either format, so a fleet can switch
+/// formats one instance at a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MessageFormat {
+    /// Bare [`Message`](crate::Message) JSON, compatible with the Go watcher
+    ///
+    /// Codecs other than JSON write an envelope with version 0 instead.
+    #[default]
+    Compat,
+
+    /// [`Envelope`](crate::Envelope) with version, timestamp and sender
+    Envelope,
+}
+
+/// How much of a message payload is written to the logs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PayloadLogging {
+    /// Only the payload size is logged
+    #[default]
+    Off,
+
+    /// Method, sender, section and policy type are logged, rule contents are not
+    Redacted,
+
+    /// The full payload is logged, including subjects, objects and actions
+    Full,
+}
+
+/// Configuration for [`DeliveryMode::Streams`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamOptions {
+    /// Approximate maximum number of entries kept in the stream (`MAXLEN ~`)
+    pub max_len: usize,
+
+    /// Prefix of the consumer group used to read the stream
+    ///
+    /// Without a group, the last-seen entry ID lives in memory and survives
+    /// reconnects only. With a group, progress is stored in Redis: entries
+    /// are acknowledged once delivered and every callback they started has
+    /// finished, and unacknowledged entries are redelivered after a restart.
+    ///
+    /// A group hands each entry to a single consumer, so every instance reads
+    /// through its own group, named `<prefix>:<consumer name>`. Instances
+    /// sharing a prefix still each receive every entry.
+    pub consumer_group: Option<String>,
+
+    /// Consumer name inside the group, defaults to the watcher's local ID
+    ///
+    /// It also names the group, so it must stay the same across restarts for
+    /// unacknowledged entries to be redelivered.
+    pub consumer_name: Option<String>,
+
+    /// How long a single XREAD blocks waiting for new entries
+    pub block_timeout: Duration,
+
+    /// Maximum number of entries fetched per XREAD
+    pub batch_size: usize,
+}
+
+impl Default for StreamOptions {
+    fn default() -> Self {
+        Self {
+            max_len: 1000,
+            consumer_group: None,
+            consumer_name: None,
+            block_timeout: Duration::from_secs(5),
+            batch_size: 100,
+        }
+    }
+}
+
+impl StreamOptions {
+    /// Create new StreamOptions with defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the approximate maximum stream length
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Read the stream through a consumer group of this instance, named after `group`
+    pub fn with_consumer_group(mut self, group: String) -> Self {
+        self.consumer_group = Some(group);
+        self
+    }
+
+    /// Set the consumer name used inside the consumer group
+    pub fn with_consumer_name(mut self, name: String) -> Self {
+        self.consumer_name = Some(name);
+        self
+    }
+
+    /// Set how long a single read blocks waiting for new entries
+    pub fn with_block_timeout(mut self, block_timeout: Duration) -> Self {
+        self.block_timeout = block_timeout;
+        self
+    }
+
+    /// Set the maximum number of entries fetched per read
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+}
+
+/// Reconnection policy for the subscription worker
+///
+/// Delays grow exponentially from `initial_delay` up to `max_delay`. With
+/// `jitter` enabled, each delay is randomized between half and the full
+/// value so that many instances don't reconnect in lockstep.
+///
+/// A subscription that ends before delivering a message or staying up for
+/// `healthy_after` counts as a failed attempt, so a stream that keeps ending
+/// right away is resubscribed with growing delays too.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectOptions {
+    /// Delay before the first reconnection attempt
+    pub initial_delay: Duration,
+
+    /// Upper bound for the delay between attempts
+    pub max_delay: Duration,
+
+    /// Factor applied to the delay after each failed attempt
+    pub multiplier: f64,
+
+    /// Whether to randomize delays
+    pub jitter: bool,
+
+    /// Maximum number of consecutive failed attempts, `None` retries forever
+    pub max_retries: Option<u32>,
+
+    /// How long a subscription must stay up to reset the backoff
+    pub healthy_after: Duration,
+}
+
+impl Default for ReconnectOptions {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: true,
+            max_retries: None,
+            healthy_after: Duration::from_secs(5),
+        }
+    }
+}
+
+impl ReconnectOptions {
+    /// Create new ReconnectOptions with defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the delay before the first reconnection attempt
+    pub fn with_initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    /// Set the upper bound for the delay between attempts
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Set the factor applied to the delay after each failed attempt
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Set whether delays are randomized
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set the maximum number of consecutive failed attempts (`None` for unlimited)
+    pub fn with_max_retries(mut self, max_retries: Option<u32>) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set how long a subscription must stay up to reset the backoff
+    pub fn with_healthy_after(mut self, healthy_after: Duration) -> Self {
+        self.healthy_after = healthy_after;
+        self
+    }
+
+    /// Delay to wait before reconnection attempt number `attempt` (starting at 1)
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let delay = (self.initial_delay.as_secs_f64() * self.multiplier.max(1.0).powi(exponent))
+            .min(self.max_delay.as_secs_f64());
+
+        let delay = if self.jitter {
+            rand::random_range(delay / 2.0..=delay)
+        } else {
+            delay
+        };
+        // Near `Duration::MAX`, rounding can take the delay out of range
+        Duration::try_from_secs_f64(delay).map_or(self.max_delay, |delay| delay.min(self.max_delay))
+    }
+}
+
+/// HMAC-SHA256 signing of published messages
+///
+/// Messages are signed with the key identified by `key_id`. Received messages
+/// are accepted when signed with that key or with one of the verification
+/// keys, so keys can be rotated by first adding the new key as a verification
+/// key on every instance, then switching the signing key.
+#[derive(Clone)]
+pub struct SigningOptions {
+    /// Identifier of the signing key, sent along with each signature
+    pub key_id: String,
+
+    /// Shared secret used to sign published messages
+    pub key: Vec<u8>,
+
+    /// Additional keys accepted when verifying received messages, by key ID
+    pub verification_keys: HashMap<String, Vec<u8>>,
+
+    /// Whether unsigned messages are delivered instead of rejected
+    ///
+    /// Only meant for migrating a fleet to signed messages.
+    pub accept_unsigned: bool,
+}
+
+impl SigningOptions {
+    /// Sign with `key`, identified by `key_id`
+    pub fn new(key_id: String, key: Vec<u8>) -> Self {
+        Self {
+            key_id,
+            key,
+            verification_keys: HashMap::new(),
+            accept_unsigned: false,
+        }
+    }
+
+    /// Also accept messages signed with `key`, identified by `key_id`
+    pub fn with_verification_key(mut self, key_id: String, key: Vec<u8>) -> Self {
+        self.verification_keys.insert(key_id, key);
+        self
+    }
+
+    /// Set whether unsigned messages are delivered instead of rejected
+    pub fn with_accept_unsigned(mut self, accept_unsigned: bool) -> Self {
+        self.accept_unsigned = accept_unsigned;
+        self
+    }
+
+    /// Key registered under `key_id`, if any
+    pub(crate) fn key(&self, key_id: &str) -> Option<&[u8]> {
+        if key_id == self.key_id {
+            Some(&self.key)
+        } else {
+            self.verification_keys.get(key_id).map(Vec::as_slice)
+        }
+    }
+}
+
+// Keys are left out so they don't end up in logs
+impl fmt::Debug for SigningOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut verification_key_ids: Vec<_> = self.verification_keys.keys().collect();
+        verification_key_ids.sort();
+        f.debug_struct("SigningOptions")
+            .field("key_id", &self.key_id)
+            .field("verification_key_ids", &verification_key_ids)
+            .field("accept_unsigned", &self.accept_unsigned)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Encryption of published messages with ChaCha20-Poly1305
+///
+/// The sender ID stays readable on the wire, the rest of the message is
+/// encrypted with the current key of the [`KeyProvider`].
+#[derive(Clone)]
+pub struct EncryptionOptions {
+    /// Source of encryption and decryption keys
+    pub provider: Arc<dyn KeyProvider>,
+
+    /// Whether unencrypted messages are delivered instead of rejected
+    ///
+    /// Only meant for migrating a fleet to encrypted messages.
+    pub accept_plaintext: bool,
+}
+
+impl EncryptionOptions {
+    /// Encrypt with keys from `provider`
+    pub fn new(provider: Arc<dyn KeyProvider>) -> Self {
+        Self {
+            provider,
+            accept_plaintext: false,
+        }
+    }
+
+    /// Set whether unencrypted messages are delivered instead of rejected
+    pub fn with_accept_plaintext(mut self, accept_plaintext: bool) -> Self {
+        self.accept_plaintext = accept_plaintext;
+        self
+    }
+}
+
+impl fmt::Debug for EncryptionOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncryptionOptions")
+            .field("accept_plaintext", &self.accept_plaintext)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Algorithm used to compress large payloads
+///
+/// Each algorithm requires the cargo feature of the same name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionAlgorithm {
+    /// Zstandard, the best ratio for policy batches
+    Zstd,
+
+    /// Gzip, widely available outside Rust
+    Gzip,
+
+    /// LZ4 frames, the fastest to compress and decompress
+    Lz4,
+}
+
+impl CompressionAlgorithm {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            CompressionAlgorithm::Zstd => "zstd",
+            CompressionAlgorithm::Gzip => "gzip",
+            CompressionAlgorithm::Lz4 => "lz4",
+        }
+    }
+}
+
+/// Compression of published payloads above a size threshold
+///
+/// Compressed payloads are detected automatically on receive, whether or not
+/// compression is enabled on the receiving instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompressionOptions {
+    /// Compression algorithm
+    pub algorithm: CompressionAlgorithm,
+
+    /// Payloads smaller than this many bytes are sent uncompressed
+    pub threshold: usize,
+}
+
+impl CompressionOptions {
+    /// Compress payloads of at least 16 KiB with `algorithm`
+    pub fn new(algorithm: CompressionAlgorithm) -> Self {
+        Self {
+            algorithm,
+            threshold: 16 * 1024,
+        }
+    }
+
+    /// Set the size from which payloads are compressed
+    pub fn with_threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+}
+
+/// Splitting of published payloads larger than a Redis pub/sub message should be
+///
+/// Chunked payloads are reassembled automatically on receive, whether or not
+/// chunking is enabled on the receiving instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkingOptions {
+    /// Largest payload published as a single message, in bytes
+    ///
+    /// Larger payloads are split into chunks of at most this size.
+    pub max_size: usize,
+
+    /// How long a receiver waits for the remaining chunks of a message
+    ///
+    /// Messages still incomplete after this long are discarded.
+    pub reassembly_timeout: Duration,
+
+    /// Largest number of messages a receiver reassembles at once
+    ///
+    /// When a chunk of another message arrives, the oldest incomplete
+    /// message is discarded to make room.
+    pub max_transfers: usize,
+
+    /// Largest number of bytes a receiver buffers for incomplete messages
+    ///
+    /// Counts the buffered chunks along with the transfer IDs announced by
+    /// the senders. Oldest incomplete messages are discarded until they fit
+    /// again, so this must be larger than the largest message.
+    pub max_buffered_bytes: usize,
+}
+
+impl ChunkingOptions {
+    /// Reassembly timeout of receivers without chunking options
+    pub(crate) const DEFAULT_REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// Concurrent transfers of receivers without chunking options
+    pub(crate) const DEFAULT_MAX_TRANSFERS: usize = 64;
+
+    /// Buffered bytes of receivers without chunking options
+    pub(crate) const DEFAULT_MAX_BUFFERED_BYTES: usize = 64 * 1024 * 1024;
+
+    /// Split payloads larger than `max_size` bytes
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            max_size,
+            reassembly_timeout: Self::DEFAULT_REASSEMBLY_TIMEOUT,
+            max_transfers: Self::DEFAULT_MAX_TRANSFERS,
+            max_buffered_bytes: Self::DEFAULT_MAX_BUFFERED_BYTES,
+        }
+    }
+
+    /// Set how long receivers wait for the remaining chunks of a message
+    pub fn with_reassembly_timeout(mut self, reassembly_timeout: Duration) -> Self {
+        self.reassembly_timeout = reassembly_timeout;
+        self
+    }
+
+    /// Set how many messages receivers reassemble at once
+    pub fn with_max_transfers(mut self, max_transfers: usize) -> Self {
+        self.max_transfers = max_transfers;
+        self
+    }
+
+    /// Set how many bytes receivers buffer for incomplete messages
+    pub fn with_max_buffered_bytes(mut self, max_buffered_bytes: usize) -> Self {
+        self.max_buffered_bytes = max_buffered_bytes;
+        self
+    }
+}
+
+/// Replay of recently published messages to instances that (re)subscribe
+///
+/// Publishers append every message to a capped history next to the channel.
+/// Subscribers read it before going live and replay the entries appended
+/// after the newest one they saw, skipping those they already received live.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryOptions {
+    /// Number of most recent messages kept in the history
+    pub max_len: usize,
+
+    /// How long the history is kept after the last publish
+    ///
+    /// `Duration::MAX` keeps it forever.
+    pub ttl: Duration,
+
+    /// Replay messages published since this time on the first subscribe
+    ///
+    /// By default a new instance replays nothing, as it is subscribed before
+    /// reading the history. Later resubscriptions always replay the entries
+    /// appended since the previous read, whatever the publishers' clocks.
+    pub replay_since: Option<SystemTime>,
+}
+
+impl Default for HistoryOptions {
+    fn default() -> Self {
+        Self {
+            max_len: 1000,
+            ttl: Duration::from_secs(3600),
+            replay_since: None,
+        }
+    }
+}
+
+impl HistoryOptions {
+    /// Create new HistoryOptions with defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of messages kept in the history
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Set how long the history is kept after the last publish
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Replay messages published since `replay_since` on the first subscribe
+    pub fn with_replay_since(mut self, replay_since: SystemTime) -> Self {
+        self.replay_since = Some(replay_since);
+        self
+    }
+}
+
+/// Batching of queued messages into a single published payload
+///
+/// Receivers unpack batches whether or not batching is enabled on the
+/// receiving instance, but versions without batch support reject them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchingOptions {
+    /// How long the publisher waits for more messages after the first one
+    ///
+    /// `Duration::MAX` waits until the batch is full.
+    pub linger: Duration,
+
+    /// Largest number of messages published as one batch
+    pub max_batch: usize,
+}
+
+impl Default for BatchingOptions {
+    fn default() -> Self {
+        Self {
+            linger: Duration::from_millis(5),
+            max_batch: 100,
+        }
+    }
+}
+
+impl BatchingOptions {
+    /// Create new BatchingOptions with defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how long the publisher waits for more messages after the first one
+    pub fn with_linger(mut self, linger: Duration) -> Self {
+        self.linger = linger;
+        self
+    }
+
+    /// Set the largest number of messages published as one batch
+    pub fn with_max_batch(mut self, max_batch: usize) -> Self {
+        self.max_batch = max_batch;
+        self
+    }
+}
+
+/// What happens to a message published while the publish queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Drop the oldest queued message to make room for the new one
+    DropOldest,
+
+    /// Reject the new message with [`WatcherError::QueueFull`](crate::WatcherError::QueueFull)
+    DropNewest,
+
+    /// Wait until the publish task takes a message off the queue
+    ///
+    /// [`RedisWatcher::publish`](crate::RedisWatcher::publish) waits
+    /// asynchronously. `update` and the other synchronous `Watcher` methods
+    /// block the calling thread, which requires a multi-threaded runtime;
+    /// on a current-thread runtime they reject the message instead.
+    Block,
+
+    /// Replace every queued message and the new one by a single
+    /// [`UpdateType::Update`](crate::UpdateType::Update)
+    ///
+    /// Receivers reload the whole policy, so no change is lost. Callers
+    /// waiting on a collapsed message get the outcome of the `Update`.
+    #[default]
+    Collapse,
+}
+
+/// Bound on the number of messages waiting to be published
+///
+/// Messages are queued while Redis is unreachable. Once `capacity` messages
+/// are waiting, `overflow` decides what happens to the next one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublishQueueOptions {
+    /// Largest number of messages waiting to be published
+    pub capacity: usize,
+
+    /// What happens to a message published while the queue is full
+    pub overflow: OverflowPolicy,
+}
+
+impl PublishQueueOptions {
+    /// Queue at most `capacity` messages, collapsing them once full
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            overflow: OverflowPolicy::default(),
+        }
+    }
+
+    /// Set what happens to a message published while the queue is full
+    pub fn with_overflow(mut self, overflow: OverflowPolicy) -> Self {
+        self.overflow = overflow;
+        self
+    }
+}
+
+/// Coalescing of bursts of received messages into a single delivery
+///
+/// A batch is delivered once `window` has passed since its first message, or
+/// as soon as it holds `max_batch` messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoalescingOptions {
+    /// How long messages are held back after the first one of a batch
+    ///
+    /// `Duration::MAX` holds them back until the batch is full or the
+    /// subscription ends.
+    pub window: Duration,
+
+    /// Deliver a batch as soon as it holds this many messages
+    pub max_batch: Option<usize>,
+}
+
+impl CoalescingOptions {
+    /// Coalesce the messages received within `window`
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            max_batch: None,
+        }
+    }
+
+    /// Deliver a batch as soon as it holds `max_batch` messages
+    pub fn with_max_batch(mut self, max_batch: usize) -> Self {
+        self.max_batch = Some(max_batch);
+        self
+    }
+}
+
 /// Configuration options for the Redis watcher
 /// This mirrors the Go version's WatcherOptions structure
 #[derive(Debug, Clone)]
@@ -26,6 +678,76 @@ pub struct WatcherOptions {
 
     /// Local instance ID
     pub local_id: String,
+
+    /// Delivery mechanism used by the Redis transport
+    pub delivery_mode: DeliveryMode,
+
+    /// Reconnection policy when the subscription is lost
+    pub reconnect: ReconnectOptions,
+
+    /// Maximum number of async callback invocations running at once
+    ///
+    /// With 1, invocations are awaited one after another in message order.
+    pub callback_concurrency: usize,
+
+    /// How long [`RedisWatcher::close`](crate::RedisWatcher::close) waits for
+    /// pending publishes, the subscription and running async callbacks to shut down
+    pub close_timeout: Duration,
+
+    /// How much of each published and received payload is logged
+    pub payload_logging: PayloadLogging,
+
+    /// Wire format of published messages
+    pub message_format: MessageFormat,
+
+    /// Serialization of published messages
+    ///
+    /// Received messages are decoded with whichever built-in codec or this
+    /// codec produced them, so a fleet can switch codecs one instance at a time.
+    pub codec: Arc<dyn Codec>,
+
+    /// Compression of large published messages
+    pub compression: Option<CompressionOptions>,
+
+    /// Splitting of oversized messages into chunks
+    pub chunking: Option<ChunkingOptions>,
+
+    /// Bound on the number of messages waiting to be published
+    ///
+    /// Without it, messages are queued without limit while Redis is unreachable.
+    pub publish_queue: Option<PublishQueueOptions>,
+
+    /// Batching of queued messages into a single published payload
+    pub batching: Option<BatchingOptions>,
+
+    /// Whether a message received after missed ones is delivered as a full reload
+    ///
+    /// Missed messages are detected from the sequence numbers of each sender
+    /// and always reported as [`WatcherEvent::GapDetected`](crate::WatcherEvent::GapDetected)
+    /// or [`WatcherEvent::SenderRestarted`](crate::WatcherEvent::SenderRestarted).
+    /// When enabled, the message is also replaced by an
+    /// [`UpdateType::Update`](crate::UpdateType::Update) from the same sender.
+    pub reload_on_gap: bool,
+
+    /// Replay of messages published while the subscription was down
+    pub history: Option<HistoryOptions>,
+
+    /// Coalescing of bursts of received messages
+    ///
+    /// Each batch is delivered once: the callback set with
+    /// [`RedisWatcher::set_batch_callback`](crate::RedisWatcher::set_batch_callback)
+    /// receives its messages, or a single
+    /// [`UpdateType::Update`](crate::UpdateType::Update) if it contained a
+    /// full reload. The update, message and async callbacks run once per
+    /// batch too: with its only message, or with an `Update` standing in for
+    /// several, so a reload callback doesn't run once per message of a burst.
+    pub coalescing: Option<CoalescingOptions>,
+
+    /// HMAC signing of published messages and verification of received ones
+    pub signing: Option<SigningOptions>,
+
+    /// Encryption of published messages and decryption of received ones
+    pub encryption: Option<EncryptionOptions>,
 }
 
 impl Default for WatcherOptions {
@@ -34,6 +756,22 @@ impl Default for WatcherOptions {
             channel: "/casbin".to_string(),
             ignore_self: false,
             local_id: Uuid::new_v4().to_string(),
+            delivery_mode: DeliveryMode::default(),
+            reconnect: ReconnectOptions::default(),
+            callback_concurrency: 1,
+            close_timeout: Duration::from_secs(5),
+            payload_logging: PayloadLogging::default(),
+            message_format: MessageFormat::default(),
+            codec: Arc::new(JsonCodec),
+            compression: None,
+            chunking: None,
+            publish_queue: None,
+            batching: None,
+            reload_on_gap: false,
+            history: None,
+            coalescing: None,
+            signing: None,
+            encryption: None,
         }
     }
 }
@@ -61,4 +799,143 @@ impl WatcherOptions {
         self.local_id = local_id;
         self
     }
+
+    /// Set the delivery mechanism (pub/sub or Redis Streams)
+    pub fn with_delivery_mode(mut self, delivery_mode: DeliveryMode) -> Self {
+        self.delivery_mode = delivery_mode;
+        self
+    }
+
+    /// Set the reconnection policy
+    pub fn with_reconnect(mut self, reconnect: ReconnectOptions) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+
+    /// Set how many async callback invocations may run at once
+    pub fn with_callback_concurrency(mut self, callback_concurrency: usize) -> Self {
+        self.callback_concurrency = callback_concurrency;
+        self
+    }
+
+    /// Set how long closing the watcher waits for pending publishes
+    pub fn with_close_timeout(mut self, close_timeout: Duration) -> Self {
+        self.close_timeout = close_timeout;
+        self
+    }
+
+    /// Set how much of each payload is logged
+    pub fn with_payload_logging(mut self, payload_logging: PayloadLogging) -> Self {
+        self.payload_logging = payload_logging;
+        self
+    }
+
+    /// Set the wire format of published messages
+    pub fn with_message_format(mut self, message_format: MessageFormat) -> Self {
+        self.message_format = message_format;
+        self
+    }
+
+    /// Set the serialization of published messages
+    pub fn with_codec(mut self, codec: Arc<dyn Codec>) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// Compress published messages above the configured size
+    pub fn with_compression(mut self, compression: CompressionOptions) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Split published messages larger than the configured size into chunks
+    pub fn with_chunking(mut self, chunking: ChunkingOptions) -> Self {
+        self.chunking = Some(chunking);
+        self
+    }
+
+    /// Bound the publish queue and set what happens once it is full
+    pub fn with_publish_queue(mut self, publish_queue: PublishQueueOptions) -> Self {
+        self.publish_queue = Some(publish_queue);
+        self
+    }
+
+    /// Publish messages queued within the linger window as a single batch
+    pub fn with_batching(mut self, batching: BatchingOptions) -> Self {
+        self.batching = Some(batching);
+        self
+    }
+
+    /// Set whether a message received after missed ones is delivered as a full reload
+    pub fn with_reload_on_gap(mut self, reload_on_gap: bool) -> Self {
+        self.reload_on_gap = reload_on_gap;
+        self
+    }
+
+    /// Keep a history of published messages and replay missed ones on subscribe
+    pub fn with_history(mut self, history: HistoryOptions) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Coalesce bursts of received messages into a single delivery
+    pub fn with_coalescing(mut self, coalescing: CoalescingOptions) -> Self {
+        self.coalescing = Some(coalescing);
+        self
+    }
+
+    /// Sign published messages and reject received ones without a valid signature
+    pub fn with_signing(mut self, signing: SigningOptions) -> Self {
+        self.signing = Some(signing);
+        self
+    }
+
+    /// Encrypt published messages and reject received ones that are not encrypted
+    pub fn with_encryption(mut self, encryption: EncryptionOptions) -> Self {
+        self.encryption = Some(encryption);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconnect_delay_backoff() {
+        let reconnect = ReconnectOptions::default()
+            .with_initial_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_millis(500))
+            .with_jitter(false);
+
+        assert_eq!(reconnect.delay(1), Duration::from_millis(100));
+        assert_eq!(reconnect.delay(2), Duration::from_millis(200));
+        assert_eq!(reconnect.delay(3), Duration::from_millis(400));
+        assert_eq!(reconnect.delay(4), Duration::from_millis(500));
+        assert_eq!(reconnect.delay(100), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_reconnect_delay_with_huge_max_delay() {
+        let reconnect = ReconnectOptions::default()
+            .with_max_delay(Duration::MAX)
+            .with_jitter(false);
+        assert_eq!(reconnect.delay(u32::MAX), Duration::MAX);
+
+        let reconnect = reconnect.with_jitter(true);
+        assert!(reconnect.delay(u32::MAX) >= Duration::MAX / 3);
+    }
+
+    #[test]
+    fn test_reconnect_delay_jitter_stays_in_range() {
+        let reconnect = ReconnectOptions::default()
+            .with_initial_delay(Duration::from_millis(100))
+            .with_jitter(true);
+
+        for _ in 0..100 {
+            let delay = reconnect.delay(2);
+            assert!(delay >= Duration::from_millis(100));
+            assert!(delay <= Duration::from_millis(200));
+        }
+    }
 }
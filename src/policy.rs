@@ -0,0 +1,271 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Incremental policy application
+//!
+//! Applies the change carried by a [`Message`] to an enforcer's in-memory
+//! model instead of reloading the whole policy.
+
+use crate::diagnostics::{error, warn};
+use crate::transport::Transport;
+use crate::watcher::{Message, RedisWatcher, Result, UpdateType, WatcherError};
+use casbin::{IEnforcer, InternalApi};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Apply the change described by `message` to `enforcer`
+///
+/// Policy changes are applied to the model only: auto-save and watcher
+/// notification are turned off for the duration, since the instance that
+/// published the message has already persisted and broadcast the change.
+/// `Update` and `UpdateForSavePolicy` carry no rules and reload the policy,
+/// as do unknown methods, whose effect cannot be applied incrementally.
+pub async fn apply_message<E>(enforcer: &mut E, message: &Message) -> Result<()>
+where
+    E: IEnforcer,
+{
+    if message.method.is_full_reload() {
+        enforcer.load_policy().await?;
+        return Ok(());
+    }
+
+    let auto_save = enforcer.has_auto_save_enabled();
+    let auto_notify = enforcer.has_auto_notify_watcher_enabled();
+    enforcer.enable_auto_save(false);
+    enforcer.enable_auto_notify_watcher(false);
+
+    let result = apply_rules(enforcer, message).await;
+
+    enforcer.enable_auto_save(auto_save);
+    enforcer.enable_auto_notify_watcher(auto_notify);
+    result
+}
+
+async fn apply_rules<E>(enforcer: &mut E, message: &Message) -> Result<()>
+where
+    E: IEnforcer,
+{
+    let (sec, ptype) = (message.sec.as_str(), message.ptype.as_str());
+    match message.method {
+        UpdateType::UpdateForAddPolicy => {
+            enforcer
+                .add_policy_internal(sec, ptype, message.new_rule.clone())
+                .await?;
+        }
+        UpdateType::UpdateForAddPolicies => {
+            enforcer
+                .add_policies_internal(sec, ptype, message.new_rules.clone())
+                .await?;
+        }
+        UpdateType::UpdateForRemovePolicy => {
+            enforcer
+                .remove_policy_internal(sec, ptype, message.old_rule.clone())
+                .await?;
+        }
+        UpdateType::UpdateForRemovePolicies => {
+            enforcer
+                .remove_policies_internal(sec, ptype, message.old_rules.clone())
+                .await?;
+        }
+        UpdateType::UpdateForRemoveFilteredPolicy => {
+            // Messages from this crate list the removed rules, which is exact;
+            // other implementations only send the filter.
+            if !message.old_rules.is_empty() {
+                enforcer
+                    .remove_policies_internal(sec, ptype, message.old_rules.clone())
+                    .await?;
+            } else {
+                let field_index = usize::try_from(message.field_index).map_err(|_| {
+                    WatcherError::Configuration(format!(
+                        "Invalid field index: {}",
+                        message.field_index
+                    ))
+                })?;
+                enforcer
+                    .remove_filtered_policy_internal(
+                        sec,
+                        ptype,
+                        field_index,
+                        message.field_values.clone(),
+                    )
+                    .await?;
+            }
+        }
+        UpdateType::UpdateForUpdatePolicy => {
+            enforcer
+                .remove_policy_internal(sec, ptype, message.old_rule.clone())
+                .await?;
+            enforcer
+                .add_policy_internal(sec, ptype, message.new_rule.clone())
+                .await?;
+        }
+        UpdateType::UpdateForUpdatePolicies => {
+            enforcer
+                .remove_policies_internal(sec, ptype, message.old_rules.clone())
+                .await?;
+            enforcer
+                .add_policies_internal(sec, ptype, message.new_rules.clone())
+                .await?;
+        }
+        UpdateType::Update | UpdateType::UpdateForSavePolicy | UpdateType::Unknown(_) => {
+            enforcer.load_policy().await?;
+        }
+    }
+    Ok(())
+}
+
+impl<T: Transport> RedisWatcher<T> {
+    /// Keep `enforcer` in sync by applying each received update incrementally
+    ///
+    /// This installs an async callback (see [`RedisWatcher::set_async_callback`])
+    /// running [`apply_message`]. If a change cannot be applied, the full
+    /// policy is reloaded instead.
+    ///
+    /// Updates are applied one at a time in message order, whatever
+    /// [`WatcherOptions::callback_concurrency`](crate::WatcherOptions::callback_concurrency)
+    /// says, since a removal applied before the matching addition would leave
+    /// the enforcer out of sync. The callback only holds a weak reference, so
+    /// the watcher can be installed into the enforcer it updates.
+    pub fn bind_enforcer<E>(&mut self, enforcer: Arc<RwLock<E>>)
+    where
+        E: IEnforcer + Send + Sync + 'static,
+    {
+        let enforcer = Arc::downgrade(&enforcer);
+        self.set_ordered_async_callback(move |message| {
+            let enforcer = enforcer.upgrade();
+            async move {
+                let Some(enforcer) = enforcer else {
+                    return;
+                };
+                let mut enforcer = enforcer.write().await;
+                if let Err(e) = apply_message(&mut *enforcer, &message).await {
+                    warn!(
+                        "Failed to apply {} incrementally, reloading policy: {}",
+                        message.method, e
+                    );
+                    if let Err(e) = enforcer.load_policy().await {
+                        error!("Failed to reload policy: {}", e);
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use casbin::prelude::*;
+
+    const MODEL_PATH: &str = "examples/rbac_model.conf";
+    const POLICY_PATH: &str = "examples/rbac_policy.csv";
+
+    fn rule(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    fn policy_message(method: UpdateType) -> Message {
+        let mut message = Message::new(method, "remote".to_string());
+        message.sec = "p".to_string();
+        message.ptype = "p".to_string();
+        message
+    }
+
+    #[tokio::test]
+    async fn test_apply_add_and_remove_policy() {
+        let mut e = Enforcer::new(MODEL_PATH, POLICY_PATH).await.unwrap();
+
+        let mut add = policy_message(UpdateType::UpdateForAddPolicy);
+        add.new_rule = rule(&["carol", "data3", "read"]);
+        apply_message(&mut e, &add).await.unwrap();
+        assert!(e.enforce(("carol", "data3", "read")).unwrap());
+
+        let mut remove = policy_message(UpdateType::UpdateForRemovePolicy);
+        remove.old_rule = rule(&["carol", "data3", "read"]);
+        apply_message(&mut e, &remove).await.unwrap();
+        assert!(!e.enforce(("carol", "data3", "read")).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_apply_does_not_persist_or_notify() {
+        let mut e = Enforcer::new(MODEL_PATH, POLICY_PATH).await.unwrap();
+        e.enable_auto_save(true);
+        e.enable_auto_notify_watcher(true);
+
+        let mut add = policy_message(UpdateType::UpdateForAddPolicy);
+        add.new_rule = rule(&["carol", "data3", "read"]);
+        apply_message(&mut e, &add).await.unwrap();
+
+        assert!(e.has_auto_save_enabled());
+        assert!(e.has_auto_notify_watcher_enabled());
+
+        // The file adapter was not written, so a reload drops the rule
+        e.load_policy().await.unwrap();
+        assert!(!e.enforce(("carol", "data3", "read")).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_apply_update_policy() {
+        let mut e = Enforcer::new(MODEL_PATH, POLICY_PATH).await.unwrap();
+
+        let mut update = policy_message(UpdateType::UpdateForUpdatePolicy);
+        update.old_rule = rule(&["bob", "data2", "write"]);
+        update.new_rule = rule(&["bob", "data2", "read"]);
+        apply_message(&mut e, &update).await.unwrap();
+
+        assert!(!e.enforce(("bob", "data2", "write")).unwrap());
+        assert!(e.enforce(("bob", "data2", "read")).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_apply_remove_filtered_policy() {
+        let mut e = Enforcer::new(MODEL_PATH, POLICY_PATH).await.unwrap();
+
+        let mut remove = policy_message(UpdateType::UpdateForRemoveFilteredPolicy);
+        remove.field_index = 0;
+        remove.field_values = rule(&["data2_admin"]);
+        apply_message(&mut e, &remove).await.unwrap();
+
+        assert!(!e.enforce(("alice", "data2", "read")).unwrap());
+        assert!(!e.enforce(("alice", "data2", "write")).unwrap());
+        assert!(e.enforce(("alice", "data1", "read")).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_apply_grouping_policy() {
+        let mut e = Enforcer::new(MODEL_PATH, POLICY_PATH).await.unwrap();
+
+        let mut add = Message::new(UpdateType::UpdateForAddPolicy, "remote".to_string());
+        add.sec = "g".to_string();
+        add.ptype = "g".to_string();
+        add.new_rule = rule(&["bob", "data2_admin"]);
+        apply_message(&mut e, &add).await.unwrap();
+
+        assert!(e.enforce(("bob", "data2", "read")).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_apply_negative_field_index_fails() {
+        let mut e = Enforcer::new(MODEL_PATH, POLICY_PATH).await.unwrap();
+        e.enable_auto_save(true);
+
+        let mut remove = policy_message(UpdateType::UpdateForRemoveFilteredPolicy);
+        remove.field_index = -1;
+        remove.field_values = rule(&["alice"]);
+        let result = apply_message(&mut e, &remove).await;
+
+        assert!(matches!(result, Err(WatcherError::Configuration(_))));
+        assert!(e.has_auto_save_enabled());
+    }
+}
@@ -0,0 +1,363 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Queue of messages waiting for the publish task.
+//!
+//! The queue is unbounded unless [`PublishQueueOptions`] are set. Once it is
+//! full, the overflow policy drops the oldest or the newest message, collapses
+//! the whole queue into a single full reload, or makes the caller wait for the
+//! publish task to take a message.
+
+use crate::options::{OverflowPolicy, PublishQueueOptions};
+use crate::watcher::{copied_failure, Message, PublishReceipt, UpdateType};
+use crate::{Result, WatcherError};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::Instant;
+use tokio::sync::{oneshot, Notify};
+
+/// Check that the options can be used to bound the publish queue
+pub(crate) fn validate(publish_queue: &PublishQueueOptions) -> Result<()> {
+    if publish_queue.capacity == 0 {
+        return Err(WatcherError::Configuration(
+            "Publish queue capacity must be at least 1".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// A queued message and where to report its outcome
+pub(crate) struct Outgoing {
+    pub(crate) message: Message,
+    pub(crate) queued_at: Instant,
+    /// Callers waiting for the outcome, several once messages were collapsed
+    acks: Vec<oneshot::Sender<Result<PublishReceipt>>>,
+}
+
+impl Outgoing {
+    pub(crate) fn new(
+        message: Message,
+        ack: Option<oneshot::Sender<Result<PublishReceipt>>>,
+    ) -> Self {
+        Self {
+            message,
+            queued_at: Instant::now(),
+            acks: ack.into_iter().collect(),
+        }
+    }
+
+    /// Report `result` to every caller waiting for this message
+    pub(crate) fn settle(self, result: Result<PublishReceipt>) {
+        let mut acks = self.acks.into_iter();
+        let Some(last) = acks.next_back() else {
+            return;
+        };
+        // The callers may have stopped waiting
+        for ack in acks {
+            let _ = ack.send(match &result {
+                Ok(receipt) => Ok(receipt.clone()),
+                Err(e) => Err(copied_failure(e)),
+            });
+        }
+        let _ = last.send(result);
+    }
+}
+
+/// What became of a message offered to the queue
+pub(crate) enum Offer {
+    /// The message was queued
+    Queued,
+    /// The queue was full, the message was queued after dropping these
+    ///
+    /// Collapsed messages are not dropped, so the list is empty for
+    /// [`OverflowPolicy::Collapse`].
+    Overflowed(Vec<Outgoing>),
+    /// The queue is full and the policy is to drop the new message
+    Rejected(Outgoing),
+    /// The queue is full and the policy is to wait for room
+    Full(Outgoing),
+}
+
+/// Messages waiting for the publish task, and bookkeeping shared with it
+pub(crate) struct PublishQueue {
+    options: Option<PublishQueueOptions>,
+    /// Sender of the full reload replacing collapsed messages
+    local_id: String,
+    queued: Mutex<VecDeque<Outgoing>>,
+    /// Set once the watcher started closing; the queue then only drains
+    closing: AtomicBool,
+    /// Wakes the publish task when a message is queued
+    added: Notify,
+    /// Wakes async callers waiting for room when a message is taken
+    taken: Notify,
+    /// Wakes blocking callers waiting for room when a message is taken
+    room: Condvar,
+    /// Messages queued or being published
+    pub(crate) pending: AtomicUsize,
+    /// Messages given up on after the watcher started closing
+    pub(crate) dropped: AtomicUsize,
+}
+
+impl PublishQueue {
+    pub(crate) fn new(options: Option<PublishQueueOptions>, local_id: String) -> Self {
+        Self {
+            options,
+            local_id,
+            queued: Mutex::new(VecDeque::new()),
+            closing: AtomicBool::new(false),
+            added: Notify::new(),
+            taken: Notify::new(),
+            room: Condvar::new(),
+            pending: AtomicUsize::new(0),
+            dropped: AtomicUsize::new(0),
+        }
+    }
+
+    /// Queue `outgoing`, applying the overflow policy if the queue is full
+    pub(crate) fn offer(&self, outgoing: Outgoing) -> Result<Offer> {
+        let mut queued = self.queued.lock().unwrap();
+        if self.closing.load(Ordering::SeqCst) {
+            return Err(WatcherError::AlreadyClosed);
+        }
+
+        let offer = match self.options {
+            Some(options) if queued.len() >= options.capacity => match options.overflow {
+                OverflowPolicy::DropNewest => return Ok(Offer::Rejected(outgoing)),
+                OverflowPolicy::Block => return Ok(Offer::Full(outgoing)),
+                OverflowPolicy::DropOldest => {
+                    let oldest = queued.pop_front();
+                    queued.push_back(outgoing);
+                    Offer::Overflowed(oldest.into_iter().collect())
+                }
+                OverflowPolicy::Collapse => {
+                    let collapsed = queued.len();
+                    let reload = queued.drain(..).fold(
+                        Outgoing {
+                            message: Message::new(UpdateType::Update, self.local_id.clone()),
+                            queued_at: outgoing.queued_at,
+                            acks: outgoing.acks,
+                        },
+                        |mut reload, message| {
+                            reload.queued_at = reload.queued_at.min(message.queued_at);
+                            reload.acks.extend(message.acks);
+                            reload
+                        },
+                    );
+                    queued.push_back(reload);
+                    self.pending.fetch_sub(collapsed - 1, Ordering::SeqCst);
+                    Offer::Overflowed(Vec::new())
+                }
+            },
+            _ => {
+                queued.push_back(outgoing);
+                self.pending.fetch_add(1, Ordering::SeqCst);
+                Offer::Queued
+            }
+        };
+        drop(queued);
+
+        self.added.notify_one();
+        Ok(offer)
+    }
+
+    /// Wait until the publish task takes a message or the queue closes
+    pub(crate) async fn wait_for_room(&self) {
+        let taken = self.taken.notified();
+        tokio::pin!(taken);
+        // Register before checking, so a message taken in between wakes us
+        taken.as_mut().enable();
+        if self.has_room(self.queued.lock().unwrap().len()) {
+            return;
+        }
+        taken.await;
+    }
+
+    /// Block the current thread until the queue has room or closes
+    pub(crate) fn wait_for_room_blocking(&self) {
+        let queued = self.queued.lock().unwrap();
+        let _queued = self
+            .room
+            .wait_while(queued, |queued| !self.has_room(queued.len()))
+            .unwrap();
+    }
+
+    fn has_room(&self, len: usize) -> bool {
+        self.closing.load(Ordering::SeqCst)
+            || self.options.is_none_or(|options| len < options.capacity)
+    }
+
+    /// Next message to publish, or `None` once the queue is closed and empty
+    pub(crate) async fn next(&self) -> Option<Outgoing> {
+        loop {
+            {
+                let mut queued = self.queued.lock().unwrap();
+                if let Some(outgoing) = queued.pop_front() {
+                    drop(queued);
+                    self.taken.notify_waiters();
+                    self.room.notify_all();
+                    return Some(outgoing);
+                }
+                if self.closing.load(Ordering::SeqCst) {
+                    return None;
+                }
+            }
+            self.added.notified().await;
+        }
+    }
+
+    /// Stop accepting messages and let the publish task drain the queue
+    pub(crate) fn close(&self) {
+        let queued = self.queued.lock().unwrap();
+        self.closing.store(true, Ordering::SeqCst);
+        drop(queued);
+
+        self.added.notify_one();
+        self.taken.notify_waiters();
+        self.room.notify_all();
+    }
+
+    /// Whether the watcher started closing
+    pub(crate) fn is_closing(&self) -> bool {
+        self.closing.load(Ordering::SeqCst)
+    }
+
+    /// Give up on every queued message
+    ///
+    /// Their callers are told the watcher closed.
+    pub(crate) fn clear(&self) {
+        self.queued.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(capacity: usize, overflow: OverflowPolicy) -> PublishQueue {
+        let options = PublishQueueOptions::new(capacity).with_overflow(overflow);
+        PublishQueue::new(Some(options), "local".to_string())
+    }
+
+    fn outgoing(method: UpdateType) -> Outgoing {
+        Outgoing::new(Message::new(method, "local".to_string()), None)
+    }
+
+    fn methods(queue: &PublishQueue) -> Vec<UpdateType> {
+        queue
+            .queued
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|outgoing| outgoing.message.method.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_capacity_is_validated() {
+        assert!(validate(&PublishQueueOptions::new(1)).is_ok());
+        assert!(validate(&PublishQueueOptions::new(0)).is_err());
+    }
+
+    #[test]
+    fn test_drop_policies() {
+        let oldest = queue(2, OverflowPolicy::DropOldest);
+        for method in [
+            UpdateType::UpdateForAddPolicy,
+            UpdateType::UpdateForRemovePolicy,
+        ] {
+            assert!(matches!(oldest.offer(outgoing(method)), Ok(Offer::Queued)));
+        }
+        match oldest.offer(outgoing(UpdateType::UpdateForSavePolicy)) {
+            Ok(Offer::Overflowed(dropped)) => {
+                assert_eq!(dropped.len(), 1);
+                assert_eq!(dropped[0].message.method, UpdateType::UpdateForAddPolicy);
+            }
+            _ => panic!("expected the oldest message to be dropped"),
+        }
+        assert_eq!(
+            methods(&oldest),
+            vec![
+                UpdateType::UpdateForRemovePolicy,
+                UpdateType::UpdateForSavePolicy
+            ]
+        );
+        assert_eq!(oldest.pending.load(Ordering::SeqCst), 2);
+
+        let newest = queue(1, OverflowPolicy::DropNewest);
+        assert!(matches!(
+            newest.offer(outgoing(UpdateType::UpdateForAddPolicy)),
+            Ok(Offer::Queued)
+        ));
+        assert!(matches!(
+            newest.offer(outgoing(UpdateType::UpdateForRemovePolicy)),
+            Ok(Offer::Rejected(_))
+        ));
+        assert_eq!(methods(&newest), vec![UpdateType::UpdateForAddPolicy]);
+        assert_eq!(newest.pending.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_collapsed_messages_share_the_reload_outcome() {
+        let queue = queue(2, OverflowPolicy::Collapse);
+        let mut acks = Vec::new();
+        for method in [
+            UpdateType::UpdateForAddPolicy,
+            UpdateType::UpdateForRemovePolicy,
+            UpdateType::UpdateForAddPolicy,
+        ] {
+            let (ack_tx, ack_rx) = oneshot::channel();
+            let message = Message::new(method, "local".to_string());
+            assert!(queue.offer(Outgoing::new(message, Some(ack_tx))).is_ok());
+            acks.push(ack_rx);
+        }
+        assert_eq!(methods(&queue), vec![UpdateType::Update]);
+        assert_eq!(queue.pending.load(Ordering::SeqCst), 1);
+
+        let reload = queue.next().await.unwrap();
+        assert_eq!(reload.message.id, "local");
+        reload.settle(Ok(PublishReceipt {
+            receivers: 2,
+            attempts: 1,
+            latency: Default::default(),
+        }));
+        for ack in acks {
+            assert_eq!(ack.await.unwrap().unwrap().receivers, 2);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_closed_queue_drains_then_ends() {
+        let queue = queue(1, OverflowPolicy::Block);
+        assert!(queue
+            .offer(outgoing(UpdateType::UpdateForAddPolicy))
+            .is_ok());
+        assert!(matches!(
+            queue.offer(outgoing(UpdateType::UpdateForAddPolicy)),
+            Ok(Offer::Full(_))
+        ));
+
+        queue.close();
+        // Waiting callers are released so they can notice the watcher closed
+        queue.wait_for_room().await;
+        queue.wait_for_room_blocking();
+        assert!(matches!(
+            queue.offer(outgoing(UpdateType::UpdateForAddPolicy)),
+            Err(WatcherError::AlreadyClosed)
+        ));
+
+        assert!(queue.next().await.is_some());
+        assert!(queue.next().await.is_none());
+    }
+}
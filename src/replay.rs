@@ -0,0 +1,161 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Replay of published messages from the transport history.
+//!
+//! Each history entry is the payload exactly as published, prefixed with the
+//! time it was published:
+//!
+//! ```text
+//! HISTORY <milliseconds since the Unix epoch>\n<payload>
+//! ```
+//!
+//! Entries are replayed by the [`HistoryId`](crate::transport::HistoryId)
+//! the transport assigned them, after the newest one seen at the previous
+//! replay. The publish time only positions the first replay of
+//! [`HistoryOptions::replay_since`](crate::HistoryOptions::replay_since).
+//!
+//! Messages received live since the previous replay may be in the history
+//! too, and messages buffered live while replaying may have been replayed, so
+//! payloads are only compared with each other inside that replay window.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SCHEME: &str = "HISTORY ";
+
+/// Prefix `payload` with the current time
+pub(crate) fn frame(payload: &[u8]) -> Vec<u8> {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis());
+    let header = format!("{}{}\n", SCHEME, millis);
+
+    let mut entry = Vec::with_capacity(header.len() + payload.len());
+    entry.extend_from_slice(header.as_bytes());
+    entry.extend_from_slice(payload);
+    entry
+}
+
+/// Split an entry produced by [`frame`] into publish time and payload
+pub(crate) fn parse(entry: &[u8]) -> Option<(SystemTime, &[u8])> {
+    let rest = entry.strip_prefix(SCHEME.as_bytes())?;
+    let newline = rest.iter().position(|b| *b == b'\n')?;
+    let millis: u64 = std::str::from_utf8(&rest[..newline]).ok()?.parse().ok()?;
+    Some((
+        UNIX_EPOCH + Duration::from_millis(millis),
+        &rest[newline + 1..],
+    ))
+}
+
+/// Fingerprints of payloads, counting repeated ones, capped to the newest
+pub(crate) struct Fingerprints {
+    capacity: usize,
+    order: VecDeque<u64>,
+    counts: HashMap<u64, usize>,
+}
+
+impl Fingerprints {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            order: VecDeque::new(),
+            counts: HashMap::new(),
+        }
+    }
+
+    /// Remember one more occurrence of `payload`
+    pub(crate) fn insert(&mut self, payload: &[u8]) {
+        let fingerprint = fingerprint(payload);
+        *self.counts.entry(fingerprint).or_default() += 1;
+        self.order.push_back(fingerprint);
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.forget(oldest);
+            }
+        }
+    }
+
+    /// Consume one occurrence of `payload`, returning whether there was one
+    pub(crate) fn remove(&mut self, payload: &[u8]) -> bool {
+        let fingerprint = fingerprint(payload);
+        if !self.counts.contains_key(&fingerprint) {
+            return false;
+        }
+        self.forget(fingerprint);
+        if let Some(position) = self.order.iter().position(|f| *f == fingerprint) {
+            self.order.remove(position);
+        }
+        true
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.order.clear();
+        self.counts.clear();
+    }
+
+    fn forget(&mut self, fingerprint: u64) {
+        if let Some(count) = self.counts.get_mut(&fingerprint) {
+            *count -= 1;
+            if *count == 0 {
+                self.counts.remove(&fingerprint);
+            }
+        }
+    }
+}
+
+fn fingerprint(payload: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    payload.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_and_parse_roundtrip() {
+        let before = SystemTime::now() - Duration::from_millis(1);
+        let entry = frame(b"HMAC-SHA256 k1 sig\npayload");
+        let (published_at, payload) = parse(&entry).unwrap();
+        assert!(published_at >= before && published_at <= SystemTime::now());
+        assert_eq!(payload, b"HMAC-SHA256 k1 sig\npayload");
+
+        assert!(parse(b"payload").is_none());
+        assert!(parse(b"HISTORY yesterday\npayload").is_none());
+    }
+
+    #[test]
+    fn test_fingerprints_count_repeats_and_forget_the_oldest() {
+        let mut fingerprints = Fingerprints::new(3);
+        fingerprints.insert(b"a");
+        fingerprints.insert(b"a");
+        assert!(fingerprints.remove(b"a"));
+        assert!(fingerprints.remove(b"a"));
+        assert!(!fingerprints.remove(b"a"));
+
+        fingerprints.insert(b"a");
+        fingerprints.insert(b"b");
+        fingerprints.insert(b"c");
+        fingerprints.insert(b"d");
+        assert!(!fingerprints.remove(b"a"));
+        assert!(fingerprints.remove(b"b"));
+
+        fingerprints.clear();
+        assert!(!fingerprints.remove(b"c"));
+    }
+}
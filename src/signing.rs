@@ -0,0 +1,163 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! HMAC-SHA256 signing of payloads.
+//!
+//! A signed payload is prefixed with a single header line:
+//!
+//! ```text
+//! HMAC-SHA256 <key id> <base64 signature>\n<payload>
+//! ```
+//!
+//! The signature covers the payload bytes exactly as sent, so verification
+//! doesn't depend on how the payload is serialized.
+
+use crate::options::SigningOptions;
+use crate::{Result, WatcherError};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const SCHEME: &str = "HMAC-SHA256 ";
+
+/// Check that the options can be used to sign messages
+pub(crate) fn validate(signing: &SigningOptions) -> Result<()> {
+    let key_ids = std::iter::once(&signing.key_id).chain(signing.verification_keys.keys());
+    for key_id in key_ids {
+        if key_id.is_empty() || key_id.contains(char::is_whitespace) {
+            return Err(WatcherError::Configuration(format!(
+                "Signing key ID must be non-empty and contain no whitespace: {:?}",
+                key_id
+            )));
+        }
+    }
+    if signing.key.is_empty() {
+        return Err(WatcherError::Configuration(
+            "Signing key must not be empty".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Prefix `payload` with its signature
+pub(crate) fn sign(signing: &SigningOptions, payload: Vec<u8>) -> Vec<u8> {
+    let signature = BASE64.encode(mac(&signing.key, &payload).finalize().into_bytes());
+    let header = format!("{}{} {}\n", SCHEME, signing.key_id, signature);
+
+    let mut signed = Vec::with_capacity(header.len() + payload.len());
+    signed.extend_from_slice(header.as_bytes());
+    signed.extend_from_slice(&payload);
+    signed
+}
+
+/// Verify the signature of `signed`, returning the payload it covers
+pub(crate) fn verify<'a>(signing: &SigningOptions, signed: &'a [u8]) -> Result<&'a [u8]> {
+    let Some(rest) = signed.strip_prefix(SCHEME.as_bytes()) else {
+        if signing.accept_unsigned {
+            return Ok(signed);
+        }
+        return Err(invalid("message is not signed"));
+    };
+
+    let newline = rest
+        .iter()
+        .position(|b| *b == b'\n')
+        .ok_or_else(|| invalid("malformed signature header"))?;
+    let (header, payload) = (&rest[..newline], &rest[newline + 1..]);
+    let header = std::str::from_utf8(header).map_err(|_| invalid("malformed signature header"))?;
+    let (key_id, signature) = header
+        .split_once(' ')
+        .ok_or_else(|| invalid("malformed signature header"))?;
+
+    let key = signing
+        .key(key_id)
+        .ok_or_else(|| invalid(&format!("unknown key ID {:?}", key_id)))?;
+    let signature = BASE64
+        .decode(signature)
+        .map_err(|_| invalid("malformed signature"))?;
+    mac(key, payload)
+        .verify_slice(&signature)
+        .map_err(|_| invalid(&format!("signature mismatch for key ID {:?}", key_id)))?;
+    Ok(payload)
+}
+
+fn mac(key: &[u8], payload: &[u8]) -> HmacSha256 {
+    // HMAC accepts keys of any length
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC key of any length");
+    mac.update(payload);
+    mac
+}
+
+fn invalid(reason: &str) -> WatcherError {
+    WatcherError::InvalidSignature(reason.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signing() -> SigningOptions {
+        SigningOptions::new("k1".to_string(), b"secret".to_vec())
+    }
+
+    #[test]
+    fn test_sign_and_verify_roundtrip() {
+        let signed = sign(&signing(), b"{\"Method\":\"Update\"}".to_vec());
+        assert!(signed.starts_with(b"HMAC-SHA256 k1 "));
+        assert_eq!(
+            verify(&signing(), &signed).unwrap(),
+            b"{\"Method\":\"Update\"}"
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_tampered_payload() {
+        let mut signed = sign(&signing(), b"{\"Method\":\"Update\"}".to_vec());
+        let last = signed.len() - 2;
+        signed[last] = b'X';
+        assert!(matches!(
+            verify(&signing(), &signed),
+            Err(WatcherError::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_accepts_rotated_keys() {
+        let old = SigningOptions::new("k0".to_string(), b"old".to_vec());
+        let signed = sign(&old, b"payload".to_vec());
+
+        assert!(verify(&signing(), &signed).is_err());
+        let rotated = signing().with_verification_key("k0".to_string(), b"old".to_vec());
+        assert_eq!(verify(&rotated, &signed).unwrap(), b"payload");
+    }
+
+    #[test]
+    fn test_verify_unsigned_payload() {
+        assert!(verify(&signing(), b"payload").is_err());
+        let lenient = signing().with_accept_unsigned(true);
+        assert_eq!(verify(&lenient, b"payload").unwrap(), b"payload");
+    }
+
+    #[test]
+    fn test_validate_rejects_key_id_with_whitespace() {
+        let signing = SigningOptions::new("key 1".to_string(), b"secret".to_vec());
+        assert!(matches!(
+            validate(&signing),
+            Err(WatcherError::Configuration(_))
+        ));
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Persistent connection for [`RedisTransport`](super::RedisTransport) commands
+
+use crate::diagnostics::debug;
+use crate::Result;
+use ::redis::aio::MultiplexedConnection;
+use ::redis::{Client, ConnectionAddr};
+use tokio::sync::Mutex;
+
+/// Long-lived multiplexed connection for regular commands
+///
+/// The connection is established on first use and shared by every command
+/// afterwards. It is re-established once a command on it fails, or when the
+/// client points to another node, e.g. after a Sentinel failover.
+#[derive(Default)]
+pub(super) struct SharedConnection {
+    connection: Mutex<Option<(ConnectionAddr, MultiplexedConnection)>>,
+}
+
+impl SharedConnection {
+    /// Connection to the node of `client`
+    pub(super) async fn get(&self, client: &Client) -> Result<MultiplexedConnection> {
+        let addr = &client.get_connection_info().addr;
+        let mut connection = self.connection.lock().await;
+        if let Some((cached_addr, conn)) = connection.as_ref() {
+            if cached_addr == addr {
+                return Ok(conn.clone());
+            }
+        }
+
+        debug!("Connecting to Redis at {}", addr);
+        let conn = client.get_multiplexed_async_connection().await?;
+        *connection = Some((addr.clone(), conn.clone()));
+        Ok(conn)
+    }
+
+    /// Drop the connection so the next command reconnects
+    pub(super) async fn invalidate(&self) {
+        self.connection.lock().await.take();
+    }
+}
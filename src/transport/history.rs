@@ -0,0 +1,129 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Replay history for [`RedisTransport`](super::RedisTransport), kept in a
+//! capped Redis stream next to the channel
+//!
+//! Stream IDs are assigned by the server, so they order entries the same way
+//! for every reader.
+
+use super::HistoryId;
+use crate::diagnostics::warn;
+use crate::Result;
+use ::redis::aio::ConnectionLike;
+use ::redis::streams::{StreamMaxlen, StreamRangeReply};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Field holding the entry in each stream entry
+const ENTRY_FIELD: &str = "entry";
+
+/// Key of the stream holding the history of `channel`
+pub(super) fn key(channel: &str) -> String {
+    format!("{}:history", channel)
+}
+
+/// Append `entry`, keeping the newest `max_len` entries for `ttl`
+pub(super) async fn append<C>(
+    conn: &mut C,
+    channel: &str,
+    entry: Vec<u8>,
+    max_len: usize,
+    ttl: Duration,
+) -> Result<()>
+where
+    C: ConnectionLike + Send,
+{
+    let key = key(channel);
+    let mut pipe = ::redis::pipe();
+    pipe.atomic()
+        .xadd_maxlen(
+            &key,
+            StreamMaxlen::Equals(max_len.max(1)),
+            "*",
+            &[(ENTRY_FIELD, entry)],
+        )
+        .ignore();
+    match expiry(ttl) {
+        Some(ttl) => pipe.pexpire(&key, ttl).ignore(),
+        None => pipe.persist(&key).ignore(),
+    };
+    pipe.query_async::<()>(conn).await?;
+    Ok(())
+}
+
+/// TTL in milliseconds, `None` when Redis would reject it as overflowing
+///
+/// Such a TTL keeps the history forever.
+fn expiry(ttl: Duration) -> Option<i64> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis());
+    let ttl = i64::try_from(ttl.as_millis()).ok()?.max(1);
+    i64::try_from(now).ok()?.checked_add(ttl).map(|_| ttl)
+}
+
+/// All entries with their IDs, oldest first
+pub(super) async fn read<C>(conn: &mut C, channel: &str) -> Result<Vec<(HistoryId, Vec<u8>)>>
+where
+    C: ConnectionLike + Send,
+{
+    let reply: StreamRangeReply = ::redis::cmd("XRANGE")
+        .arg(key(channel))
+        .arg("-")
+        .arg("+")
+        .query_async(conn)
+        .await?;
+    Ok(reply
+        .ids
+        .into_iter()
+        .filter_map(|entry| {
+            let Some(id) = parse_id(&entry.id) else {
+                warn!("Skipping history entry with malformed ID {}", entry.id);
+                return None;
+            };
+            entry
+                .get::<Vec<u8>>(ENTRY_FIELD)
+                .map(|payload| (id, payload))
+        })
+        .collect())
+}
+
+/// Parse a stream ID such as `1700000000000-3`
+fn parse_id(id: &str) -> Option<HistoryId> {
+    let (millis, sequence) = id.split_once('-')?;
+    Some(HistoryId(millis.parse().ok()?, sequence.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overflowing_ttl_never_expires() {
+        assert_eq!(expiry(Duration::from_secs(60)), Some(60_000));
+        assert_eq!(expiry(Duration::ZERO), Some(1));
+        assert_eq!(expiry(Duration::from_millis(i64::MAX as u64)), None);
+        assert_eq!(expiry(Duration::MAX), None);
+    }
+
+    #[test]
+    fn test_stream_ids_are_ordered() {
+        let first = parse_id("1700000000000-9").unwrap();
+        let second = parse_id("1700000000001-0").unwrap();
+        assert_eq!(first, HistoryId(1_700_000_000_000, 9));
+        assert!(first < second);
+        assert!(parse_id("1700000000000").is_none());
+        assert!(parse_id("a-b").is_none());
+    }
+}
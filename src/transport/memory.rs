@@ -0,0 +1,233 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{HistoryId, PayloadStream, Transport};
+use crate::diagnostics::warn;
+use crate::Result;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+use tokio_stream::StreamExt;
+
+/// Default number of payloads buffered per channel for slow subscribers
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// In-process transport that mimics Redis pub/sub semantics
+///
+/// Clones share the same set of channels, so several watchers created from
+/// clones of one `MemoryTransport` exchange messages exactly as they would
+/// through a Redis server: subscribers only receive payloads published after
+/// they subscribed, and publishing returns the number of active subscribers.
+/// A subscriber that falls more than `capacity` payloads behind skips the
+/// missed ones, like a Redis client hitting its output buffer limit.
+/// Replay histories are kept in memory as well.
+#[derive(Clone)]
+pub struct MemoryTransport {
+    channels: Arc<Mutex<HashMap<String, broadcast::Sender<Vec<u8>>>>>,
+    histories: Arc<Mutex<HashMap<String, History>>>,
+    /// Last history ID handed out, kept across expired histories
+    history_id: Arc<AtomicU64>,
+    capacity: usize,
+}
+
+/// Replay history of a channel
+struct History {
+    entries: VecDeque<(HistoryId, Vec<u8>)>,
+    /// `None` for a TTL too long to represent, which never expires
+    expires_at: Option<Instant>,
+}
+
+impl History {
+    fn is_live(&self, now: Instant) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+impl MemoryTransport {
+    /// Create a new in-memory transport
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create a new in-memory transport buffering up to `capacity` payloads per channel
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            channels: Arc::new(Mutex::new(HashMap::new())),
+            histories: Arc::new(Mutex::new(HashMap::new())),
+            history_id: Arc::new(AtomicU64::new(0)),
+            capacity: capacity.max(1),
+        }
+    }
+
+    fn sender(&self, channel: &str) -> broadcast::Sender<Vec<u8>> {
+        let mut channels = self.channels.lock().unwrap();
+        // Drop channels nobody listens to anymore, Redis forgets them as well
+        channels.retain(|_, tx| tx.receiver_count() > 0);
+        channels
+            .entry(channel.to_string())
+            .or_insert_with(|| broadcast::channel(self.capacity).0)
+            .clone()
+    }
+}
+
+impl Default for MemoryTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transport for MemoryTransport {
+    async fn subscribe(&self, channel: &str) -> Result<PayloadStream> {
+        let rx = self.sender(channel).subscribe();
+        let channel = channel.to_string();
+        let stream = BroadcastStream::new(rx).filter_map(move |item| match item {
+            Ok(payload) => Some(payload),
+            Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                warn!(
+                    "In-memory subscriber on channel {} lagged, {} messages skipped",
+                    channel, skipped
+                );
+                None
+            }
+        });
+        Ok(Box::pin(stream))
+    }
+
+    async fn publish(&self, channel: &str, payload: Vec<u8>) -> Result<usize> {
+        let channels = self.channels.lock().unwrap();
+        match channels.get(channel) {
+            // Sending only fails when there are no receivers, which Redis reports as 0
+            Some(tx) => Ok(tx.send(payload).unwrap_or(0)),
+            None => Ok(0),
+        }
+    }
+
+    async fn append_history(
+        &self,
+        channel: &str,
+        entry: Vec<u8>,
+        max_len: usize,
+        ttl: Duration,
+    ) -> Result<()> {
+        let mut histories = self.histories.lock().unwrap();
+        let now = Instant::now();
+        histories.retain(|_, history| history.is_live(now));
+        let history = histories
+            .entry(channel.to_string())
+            .or_insert_with(|| History {
+                entries: VecDeque::new(),
+                expires_at: Some(now),
+            });
+        let id = self.history_id.fetch_add(1, Ordering::SeqCst) + 1;
+        history.entries.push_back((HistoryId(id, 0), entry));
+        while history.entries.len() > max_len.max(1) {
+            history.entries.pop_front();
+        }
+        history.expires_at = now.checked_add(ttl);
+        Ok(())
+    }
+
+    async fn read_history(&self, channel: &str) -> Result<Vec<(HistoryId, Vec<u8>)>> {
+        let histories = self.histories.lock().unwrap();
+        Ok(histories
+            .get(channel)
+            .filter(|history| history.is_live(Instant::now()))
+            .map(|history| history.entries.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_publish_reaches_all_subscribers() {
+        let transport = MemoryTransport::new();
+        let mut s1 = transport.subscribe("chan").await.unwrap();
+        let mut s2 = transport.subscribe("chan").await.unwrap();
+
+        let receivers = transport.publish("chan", b"hello".to_vec()).await.unwrap();
+        assert_eq!(receivers, 2);
+        assert_eq!(s1.next().await.unwrap(), b"hello");
+        assert_eq!(s2.next().await.unwrap(), b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_channels_are_isolated() {
+        let transport = MemoryTransport::new();
+        let mut other = transport.subscribe("other").await.unwrap();
+
+        let receivers = transport.publish("chan", b"hello".to_vec()).await.unwrap();
+        assert_eq!(receivers, 0);
+
+        transport.publish("other", b"world".to_vec()).await.unwrap();
+        assert_eq!(other.next().await.unwrap(), b"world");
+    }
+
+    #[tokio::test]
+    async fn test_history_is_capped_and_expires() {
+        let transport = MemoryTransport::new();
+        for entry in [b"a", b"b", b"c"] {
+            transport
+                .append_history("chan", entry.to_vec(), 2, Duration::from_secs(60))
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            transport.read_history("chan").await.unwrap(),
+            vec![
+                (HistoryId(2, 0), b"b".to_vec()),
+                (HistoryId(3, 0), b"c".to_vec())
+            ]
+        );
+        assert!(transport.read_history("other").await.unwrap().is_empty());
+
+        transport
+            .append_history("chan", b"d".to_vec(), 2, Duration::ZERO)
+            .await
+            .unwrap();
+        assert!(transport.read_history("chan").await.unwrap().is_empty());
+
+        // IDs keep increasing after the history expired
+        transport
+            .append_history("chan", b"e".to_vec(), 2, Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(
+            transport.read_history("chan").await.unwrap(),
+            vec![(HistoryId(5, 0), b"e".to_vec())]
+        );
+
+        // A TTL too long to represent keeps the history
+        transport
+            .append_history("chan", b"f".to_vec(), 2, Duration::MAX)
+            .await
+            .unwrap();
+        assert_eq!(transport.read_history("chan").await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_subscriber_is_not_counted() {
+        let transport = MemoryTransport::new();
+        let stream = transport.subscribe("chan").await.unwrap();
+        drop(stream);
+
+        let receivers = transport.publish("chan", b"hello".to_vec()).await.unwrap();
+        assert_eq!(receivers, 0);
+    }
+}
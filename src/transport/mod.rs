@@ -0,0 +1,103 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Transports used by [`RedisWatcher`](crate::RedisWatcher) to exchange messages.
+//!
+//! A transport only moves opaque payloads: it subscribes to a channel and
+//! publishes bytes to it. Serialization, self-filtering and callbacks are
+//! handled by the watcher, so a custom transport only needs to implement
+//! [`Transport`].
+
+mod connection;
+mod history;
+mod memory;
+mod redis;
+mod sentinel;
+mod sharded;
+mod streams;
+
+pub use self::memory::MemoryTransport;
+pub use self::redis::RedisTransport;
+
+use crate::Result;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+use tokio_stream::Stream;
+
+/// Stream of raw payloads received on a subscribed channel
+///
+/// The stream ending means the subscription was lost.
+pub type PayloadStream = Pin<Box<dyn Stream<Item = Vec<u8>> + Send>>;
+
+/// Position of an entry in a replay history
+///
+/// Assigned by the transport when the entry is appended, so entries are
+/// ordered the same way for every reader whatever the clocks of the
+/// publishers. Redis stream IDs map to their milliseconds and sequence parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HistoryId(pub u64, pub u64);
+
+/// Publish/subscribe backend for [`RedisWatcher`](crate::RedisWatcher)
+pub trait Transport: Send + Sync + 'static {
+    /// Subscribe to `channel`
+    ///
+    /// The returned future must only resolve once the subscription is active,
+    /// so that messages published afterwards are guaranteed to be received.
+    fn subscribe(&self, channel: &str) -> impl Future<Output = Result<PayloadStream>> + Send;
+
+    /// Publish `payload` to `channel`, returning the number of receivers
+    ///
+    /// Transports that cannot tell how many receivers got the payload return 0.
+    fn publish(
+        &self,
+        channel: &str,
+        payload: Vec<u8>,
+    ) -> impl Future<Output = Result<usize>> + Send;
+
+    /// Append `entry` to the replay history of `channel`
+    ///
+    /// The history keeps the newest `max_len` entries and expires `ttl` after
+    /// the last append. Each entry gets a [`HistoryId`] greater than those of
+    /// the entries appended before it. Transports without storage for a
+    /// history keep none.
+    fn append_history(
+        &self,
+        channel: &str,
+        entry: Vec<u8>,
+        max_len: usize,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<()>> + Send {
+        let _ = (channel, entry, max_len, ttl);
+        async { Ok(()) }
+    }
+
+    /// Called once every payload received so far on `channel` has been handled
+    ///
+    /// Handled means delivered to the callbacks, including messages held back
+    /// by coalescing, with every async callback they started finished.
+    /// Transports with at-least-once delivery acknowledge the payloads then.
+    fn acknowledge(&self, channel: &str) {
+        let _ = channel;
+    }
+
+    /// Entries of the replay history of `channel` with their IDs, oldest first
+    fn read_history(
+        &self,
+        channel: &str,
+    ) -> impl Future<Output = Result<Vec<(HistoryId, Vec<u8>)>>> + Send {
+        let _ = channel;
+        async { Ok(Vec::new()) }
+    }
+}
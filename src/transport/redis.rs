@@ -0,0 +1,335 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::connection::SharedConnection;
+use super::history;
+use super::sentinel::SentinelMaster;
+use super::sharded::ShardedCluster;
+use super::streams::StreamState;
+use super::{HistoryId, PayloadStream, Transport};
+use crate::diagnostics::{debug, warn};
+use crate::options::DeliveryMode;
+use crate::{Result, WatcherError};
+use ::redis::{AsyncCommands, Client};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_stream::StreamExt;
+
+/// Wrapper to support both standalone and cluster Redis
+enum RedisClientWrapper {
+    Standalone(Client),
+    // For Cluster mode, we use a single node connection for pubsub
+    // Redis Cluster PubSub messages don't propagate across nodes,
+    // so all instances must connect to the same node for pub/sub
+    ClusterPubSub { pubsub_client: Client },
+    // Sharded pub/sub routes the channel to the node owning its slot,
+    // so instances may connect through any node
+    ShardedCluster(Box<ShardedCluster>),
+    // With Sentinel, the master is discovered at connect time and
+    // connections move to the new master after a failover
+    Sentinel(Arc<SentinelMaster>),
+}
+
+/// Delivery mechanism in use, see [`DeliveryMode`]
+enum Delivery {
+    PubSub,
+    Streams(Arc<StreamState>),
+}
+
+/// Redis transport for standalone Redis, Redis Cluster and Redis Sentinel
+///
+/// Uses pub/sub by default; [`RedisTransport::with_delivery_mode`] switches
+/// to Redis Streams for durable delivery.
+///
+/// Publishing and other regular commands share one long-lived multiplexed
+/// connection, which is re-established after a command on it fails.
+pub struct RedisTransport {
+    client: RedisClientWrapper,
+    delivery: Delivery,
+    connection: SharedConnection,
+}
+
+/// Split a comma-separated list of Redis URLs
+fn parse_urls(urls: &str) -> Result<Vec<&str>> {
+    let urls: Vec<&str> = urls
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if urls.is_empty() {
+        return Err(WatcherError::Configuration(
+            "No Redis URLs provided".to_string(),
+        ));
+    }
+    Ok(urls)
+}
+
+impl RedisTransport {
+    /// Create a transport for standalone Redis
+    pub fn standalone(redis_url: &str) -> Result<Self> {
+        Ok(Self {
+            client: RedisClientWrapper::Standalone(Client::open(redis_url)?),
+            delivery: Delivery::PubSub,
+            connection: SharedConnection::default(),
+        })
+    }
+
+    /// Create a transport for Redis Cluster
+    ///
+    /// Note: Redis Cluster PubSub messages don't propagate between nodes.
+    /// All instances MUST connect to the SAME node for pub/sub to work.
+    /// This method uses the first URL as the fixed PubSub node.
+    ///
+    /// # Arguments
+    /// * `cluster_urls` - Comma-separated Redis URLs (first URL used for PubSub)
+    pub fn cluster(cluster_urls: &str) -> Result<Self> {
+        let urls = parse_urls(cluster_urls)?;
+
+        // For Redis Cluster PubSub: use the first node for both publish and subscribe
+        // This ensures messages are sent and received on the same node
+        // since PubSub messages don't propagate across cluster nodes
+        let pubsub_url = urls[0];
+        let pubsub_client = Client::open(pubsub_url).map_err(|e| {
+            WatcherError::Configuration(format!("Failed to create pubsub client: {}", e))
+        })?;
+
+        warn!(
+            "Redis Cluster PubSub using fixed node: {} - ALL instances MUST use the SAME node!",
+            pubsub_url
+        );
+
+        Ok(Self {
+            client: RedisClientWrapper::ClusterPubSub { pubsub_client },
+            delivery: Delivery::PubSub,
+            connection: SharedConnection::default(),
+        })
+    }
+
+    /// Create a transport for Redis Cluster 7+ using sharded pub/sub
+    ///
+    /// SPUBLISH/SSUBSCRIBE are routed to the node owning the channel's hash
+    /// slot, following MOVED redirects and slot migrations. Unlike
+    /// [`RedisTransport::cluster`], instances do not need to share a node.
+    ///
+    /// # Arguments
+    /// * `cluster_urls` - Comma-separated Redis URLs used to discover the cluster
+    pub fn sharded_cluster(cluster_urls: &str) -> Result<Self> {
+        let urls = parse_urls(cluster_urls)?;
+        let nodes = urls.into_iter().map(str::to_string).collect();
+
+        Ok(Self {
+            client: RedisClientWrapper::ShardedCluster(Box::new(ShardedCluster::new(nodes)?)),
+            delivery: Delivery::PubSub,
+            connection: SharedConnection::default(),
+        })
+    }
+
+    /// Create a transport for a Sentinel-monitored master
+    ///
+    /// The master is discovered through the sentinels, and both publishing
+    /// and subscribing follow it to the new master on `+switch-master`.
+    ///
+    /// # Arguments
+    /// * `sentinel_urls` - Comma-separated Sentinel URLs
+    /// * `master_name` - Name of the master monitored by the sentinels
+    pub fn sentinel(sentinel_urls: &str, master_name: &str) -> Result<Self> {
+        let urls = parse_urls(sentinel_urls)?;
+        let urls = urls.into_iter().map(str::to_string).collect();
+
+        Ok(Self {
+            client: RedisClientWrapper::Sentinel(Arc::new(SentinelMaster::new(urls, master_name)?)),
+            delivery: Delivery::PubSub,
+            connection: SharedConnection::default(),
+        })
+    }
+
+    /// Select how messages are delivered (pub/sub or Redis Streams)
+    pub fn with_delivery_mode(mut self, mode: DeliveryMode) -> Self {
+        self.delivery = match mode {
+            DeliveryMode::PubSub => Delivery::PubSub,
+            DeliveryMode::Streams(options) => {
+                Delivery::Streams(Arc::new(StreamState::new(options)))
+            }
+        };
+        self
+    }
+}
+
+impl RedisTransport {
+    /// Subscribe through a single Redis node
+    async fn subscribe_node(&self, client: &Client, channel: &str) -> Result<PayloadStream> {
+        if let Delivery::Streams(state) = &self.delivery {
+            // XREAD BLOCK holds the connection, so readers get their own
+            let conn = client.get_multiplexed_async_connection().await?;
+            return state.subscribe(conn, channel).await;
+        }
+
+        let mut pubsub = client.get_async_pubsub().await?;
+        pubsub.subscribe(channel).await?;
+
+        let stream = pubsub
+            .into_on_message()
+            .map(|msg| msg.get_payload_bytes().to_vec());
+        Ok(Box::pin(stream))
+    }
+
+    /// Publish through a single Redis node
+    async fn publish_node(
+        &self,
+        client: &Client,
+        channel: &str,
+        payload: Vec<u8>,
+    ) -> Result<usize> {
+        let mut conn = self.connection.get(client).await?;
+        let result = match &self.delivery {
+            // XADD does not report readers, the entry is durable once stored
+            Delivery::Streams(state) => state.publish(&mut conn, channel, payload).await.map(|_| 0),
+            Delivery::PubSub => conn.publish(channel, payload).await.map_err(Into::into),
+        };
+        self.checked(result).await
+    }
+
+    /// Reconnect on the next command if `result` is an error
+    async fn checked<R>(&self, result: Result<R>) -> Result<R> {
+        if result.is_err() {
+            self.connection.invalidate().await;
+        }
+        result
+    }
+}
+
+impl Transport for RedisTransport {
+    async fn subscribe(&self, channel: &str) -> Result<PayloadStream> {
+        match &self.client {
+            RedisClientWrapper::Standalone(client) => self.subscribe_node(client, channel).await,
+            // Use the dedicated pubsub client for cluster mode
+            RedisClientWrapper::ClusterPubSub { pubsub_client } => {
+                self.subscribe_node(pubsub_client, channel).await
+            }
+            RedisClientWrapper::ShardedCluster(cluster) => match &self.delivery {
+                Delivery::Streams(state) => {
+                    let conn = cluster.dedicated_connection().await?;
+                    state.subscribe(conn, channel).await
+                }
+                Delivery::PubSub => cluster.subscribe(channel).await,
+            },
+            RedisClientWrapper::Sentinel(sentinel) => {
+                // Watch for failovers first so that none is missed while subscribing
+                let failover = sentinel.watch_failover().await?;
+                let master = sentinel.master().await?;
+                let stream = match self.subscribe_node(&master, channel).await {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        sentinel.invalidate();
+                        return Err(e);
+                    }
+                };
+                // End the subscription on failover, the watcher then resubscribes to the new master
+                Ok(Box::pin(futures_util::StreamExt::take_until(
+                    stream,
+                    Box::pin(failover),
+                )))
+            }
+        }
+    }
+
+    async fn publish(&self, channel: &str, payload: Vec<u8>) -> Result<usize> {
+        match &self.client {
+            RedisClientWrapper::Standalone(client) => {
+                self.publish_node(client, channel, payload).await
+            }
+            // For Redis Cluster, we need to publish to the same node where PubSub is subscribed
+            // because PubSub messages don't propagate across cluster nodes
+            RedisClientWrapper::ClusterPubSub { pubsub_client } => {
+                debug!("Publishing to cluster node via pubsub_client");
+                self.publish_node(pubsub_client, channel, payload).await
+            }
+            RedisClientWrapper::ShardedCluster(cluster) => match &self.delivery {
+                Delivery::Streams(state) => {
+                    let mut conn = cluster.connection().await?;
+                    state.publish(&mut conn, channel, payload).await?;
+                    Ok(0)
+                }
+                Delivery::PubSub => cluster.publish(channel, payload).await,
+            },
+            RedisClientWrapper::Sentinel(sentinel) => {
+                let master = sentinel.master().await?;
+                let result = self.publish_node(&master, channel, payload).await;
+                if result.is_err() {
+                    // The master may have moved, ask the sentinels again on retry
+                    sentinel.invalidate();
+                }
+                result
+            }
+        }
+    }
+
+    fn acknowledge(&self, channel: &str) {
+        if let Delivery::Streams(state) = &self.delivery {
+            state.settle(channel);
+        }
+    }
+
+    async fn append_history(
+        &self,
+        channel: &str,
+        entry: Vec<u8>,
+        max_len: usize,
+        ttl: Duration,
+    ) -> Result<()> {
+        match &self.client {
+            RedisClientWrapper::Standalone(client)
+            | RedisClientWrapper::ClusterPubSub {
+                pubsub_client: client,
+            } => {
+                let mut conn = self.connection.get(client).await?;
+                let result = history::append(&mut conn, channel, entry, max_len, ttl).await;
+                self.checked(result).await
+            }
+            RedisClientWrapper::ShardedCluster(cluster) => {
+                let mut conn = cluster.connection().await?;
+                history::append(&mut conn, channel, entry, max_len, ttl).await
+            }
+            RedisClientWrapper::Sentinel(sentinel) => {
+                let master = sentinel.master().await?;
+                let mut conn = self.connection.get(&master).await?;
+                let result = history::append(&mut conn, channel, entry, max_len, ttl).await;
+                self.checked(result).await
+            }
+        }
+    }
+
+    async fn read_history(&self, channel: &str) -> Result<Vec<(HistoryId, Vec<u8>)>> {
+        match &self.client {
+            RedisClientWrapper::Standalone(client)
+            | RedisClientWrapper::ClusterPubSub {
+                pubsub_client: client,
+            } => {
+                let mut conn = self.connection.get(client).await?;
+                let result = history::read(&mut conn, channel).await;
+                self.checked(result).await
+            }
+            RedisClientWrapper::ShardedCluster(cluster) => {
+                let mut conn = cluster.connection().await?;
+                history::read(&mut conn, channel).await
+            }
+            RedisClientWrapper::Sentinel(sentinel) => {
+                let master = sentinel.master().await?;
+                let mut conn = self.connection.get(&master).await?;
+                let result = history::read(&mut conn, channel).await;
+                self.checked(result).await
+            }
+        }
+    }
+}
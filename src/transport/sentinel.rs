@@ -0,0 +1,130 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Redis Sentinel master discovery and failover tracking
+
+use crate::diagnostics::{info, warn};
+use crate::{Result, WatcherError};
+use ::redis::sentinel::{SentinelClient, SentinelServerType};
+use ::redis::Client;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use tokio_stream::StreamExt;
+
+/// Sentinel channel announcing a new master after failover
+const SWITCH_MASTER_CHANNEL: &str = "+switch-master";
+
+/// Master of a Sentinel-monitored deployment
+///
+/// The master client is resolved through the sentinels on first use and
+/// cached until a failover is observed or a command against it fails.
+pub(super) struct SentinelMaster {
+    sentinel: tokio::sync::Mutex<SentinelClient>,
+    master_name: String,
+    master: RwLock<Option<Client>>,
+}
+
+impl SentinelMaster {
+    pub(super) fn new(sentinel_urls: Vec<String>, master_name: &str) -> Result<Self> {
+        let sentinel = SentinelClient::build(
+            sentinel_urls,
+            master_name.to_string(),
+            None,
+            SentinelServerType::Master,
+        )
+        .map_err(|e| {
+            WatcherError::Configuration(format!("Failed to create sentinel client: {}", e))
+        })?;
+
+        Ok(Self {
+            sentinel: tokio::sync::Mutex::new(sentinel),
+            master_name: master_name.to_string(),
+            master: RwLock::new(None),
+        })
+    }
+
+    /// Client for the current master
+    pub(super) async fn master(&self) -> Result<Client> {
+        if let Some(client) = self.master.read().unwrap().clone() {
+            return Ok(client);
+        }
+
+        let client = self.sentinel.lock().await.async_get_client().await?;
+        info!(
+            "Sentinel resolved master {} at {}",
+            self.master_name,
+            client.get_connection_info().addr
+        );
+        *self.master.write().unwrap() = Some(client.clone());
+        Ok(client)
+    }
+
+    /// Forget the cached master so the next call asks the sentinels again
+    pub(super) fn invalidate(&self) {
+        self.master.write().unwrap().take();
+    }
+
+    /// Subscribe to failover announcements
+    ///
+    /// The returned future resolves once the master moved, or once the
+    /// connection to the sentinel is lost and failovers can no longer be
+    /// observed. Either way the cached master is dropped, so the caller
+    /// should reconnect.
+    pub(super) async fn watch_failover(
+        self: &Arc<Self>,
+    ) -> Result<impl Future<Output = ()> + Send + 'static> {
+        let sentinel_client = self
+            .sentinel
+            .lock()
+            .await
+            .async_get_sentinel_client()
+            .await?;
+        let mut pubsub = sentinel_client.get_async_pubsub().await?;
+        pubsub.subscribe(SWITCH_MASTER_CHANNEL).await?;
+
+        let master = self.clone();
+        Ok(async move {
+            let mut stream = pubsub.into_on_message();
+            while let Some(msg) = stream.next().await {
+                let payload: String = msg.get_payload().unwrap_or_default();
+                if switched_master(&payload) == Some(master.master_name.as_str()) {
+                    warn!("Sentinel failover of {}: {}", master.master_name, payload);
+                    break;
+                }
+            }
+            master.invalidate();
+        })
+    }
+}
+
+/// Master name of a `+switch-master` event
+///
+/// The payload is `<master name> <old ip> <old port> <new ip> <new port>`.
+fn switched_master(payload: &str) -> Option<&str> {
+    payload.split_whitespace().next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_switched_master() {
+        assert_eq!(
+            switched_master("mymaster 127.0.0.1 6379 127.0.0.1 6380"),
+            Some("mymaster")
+        );
+        assert_eq!(switched_master(""), None);
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sharded pub/sub (SPUBLISH/SSUBSCRIBE) for Redis Cluster 7+
+
+use super::PayloadStream;
+use crate::diagnostics::warn;
+use crate::{Result, WatcherError};
+use ::redis::cluster::{ClusterClient, ClusterClientBuilder};
+use ::redis::cluster_async::ClusterConnection;
+use ::redis::{ProtocolVersion, PushInfo, PushKind, Value};
+use tokio::sync::{mpsc, OnceCell};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_stream::{Stream, StreamExt};
+
+/// Cluster client using sharded pub/sub
+///
+/// A sharded channel lives on the node owning its hash slot. The cluster
+/// connection routes SPUBLISH/SSUBSCRIBE to that node and follows MOVED
+/// redirects, so instances may be given any subset of the cluster nodes.
+/// A subscription ends when its connection drops, so the watcher resubscribes
+/// and replays what it missed like with any other lost subscription.
+pub(super) struct ShardedCluster {
+    nodes: Vec<String>,
+    client: ClusterClient,
+    connection: OnceCell<ClusterConnection>,
+}
+
+impl ShardedCluster {
+    pub(super) fn new(nodes: Vec<String>) -> Result<Self> {
+        let client = Self::builder(&nodes).build().map_err(|e| {
+            WatcherError::Configuration(format!("Failed to create cluster client: {}", e))
+        })?;
+        Ok(Self {
+            nodes,
+            client,
+            connection: OnceCell::new(),
+        })
+    }
+
+    fn builder(nodes: &[String]) -> ClusterClientBuilder {
+        // Subscriptions on a cluster connection require RESP3 push messages
+        ClusterClientBuilder::new(nodes.to_vec()).use_protocol(ProtocolVersion::RESP3)
+    }
+
+    /// Shared connection for regular commands, established on first use
+    pub(super) async fn connection(&self) -> Result<ClusterConnection> {
+        let conn = self
+            .connection
+            .get_or_try_init(|| self.client.get_async_connection())
+            .await?;
+        Ok(conn.clone())
+    }
+
+    /// Dedicated connection, for commands that block the connection
+    pub(super) async fn dedicated_connection(&self) -> Result<ClusterConnection> {
+        Ok(self.client.get_async_connection().await?)
+    }
+
+    pub(super) async fn subscribe(&self, channel: &str) -> Result<PayloadStream> {
+        // Push messages are delivered through the client the connection was built from,
+        // so each subscription needs its own client
+        let (tx, rx) = mpsc::unbounded_channel::<PushInfo>();
+        let client = Self::builder(&self.nodes).push_sender(tx).build()?;
+        let mut conn = client.get_async_connection().await?;
+        conn.ssubscribe(channel).await?;
+
+        let stream = payloads(channel.to_string(), UnboundedReceiverStream::new(rx));
+        // Keep the connection alive for as long as the stream is polled
+        Ok(Box::pin(stream.map(move |payload| {
+            let _ = &conn;
+            payload
+        })))
+    }
+
+    pub(super) async fn publish(&self, channel: &str, payload: Vec<u8>) -> Result<usize> {
+        let mut conn = self.connection().await?;
+        let receivers: usize = ::redis::cmd("SPUBLISH")
+            .arg(channel)
+            .arg(payload)
+            .query_async(&mut conn)
+            .await?;
+        Ok(receivers)
+    }
+}
+
+/// Payloads of the `smessage` pushes for `channel`, ending at the first disconnection
+fn payloads(channel: String, pushes: impl Stream<Item = PushInfo>) -> impl Stream<Item = Vec<u8>> {
+    pushes
+        .map_while(move |push| match push.kind {
+            PushKind::SMessage => Some(sharded_payload(&channel, push.data)),
+            PushKind::Disconnection => {
+                warn!(
+                    "Sharded subscription to {} disconnected, resubscribing",
+                    channel
+                );
+                None
+            }
+            _ => Some(None),
+        })
+        .filter_map(|payload| payload)
+}
+
+/// Extract the payload of an `smessage` push for `channel`
+fn sharded_payload(channel: &str, data: Vec<Value>) -> Option<Vec<u8>> {
+    let mut data = data.into_iter();
+    let name: String = ::redis::from_redis_value(&data.next()?).ok()?;
+    if name != channel {
+        return None;
+    }
+    ::redis::from_redis_value(&data.next()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push(kind: PushKind, data: Vec<Value>) -> PushInfo {
+        PushInfo { kind, data }
+    }
+
+    fn smessage(channel: &str, payload: &[u8]) -> PushInfo {
+        push(
+            PushKind::SMessage,
+            vec![
+                Value::BulkString(channel.as_bytes().to_vec()),
+                Value::BulkString(payload.to_vec()),
+            ],
+        )
+    }
+
+    #[tokio::test]
+    async fn test_stream_ends_on_disconnection() {
+        let pushes = tokio_stream::iter(vec![
+            smessage("/casbin", b"first"),
+            smessage("/other", b"ignored"),
+            push(PushKind::Disconnection, vec![]),
+            smessage("/casbin", b"after"),
+        ]);
+        let received: Vec<Vec<u8>> = payloads("/casbin".to_string(), pushes).collect().await;
+        assert_eq!(received, vec![b"first".to_vec()]);
+    }
+}
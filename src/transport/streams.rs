@@ -0,0 +1,300 @@
+// Copyright 2025 The Casbin Authors. All Rights Reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Redis Streams delivery for [`RedisTransport`](super::RedisTransport)
+
+use super::PayloadStream;
+use crate::diagnostics::{debug, warn};
+use crate::options::StreamOptions;
+use crate::Result;
+use ::redis::aio::ConnectionLike;
+use ::redis::streams::{
+    StreamId, StreamMaxlen, StreamRangeReply, StreamReadOptions, StreamReadReply,
+};
+use ::redis::AsyncCommands;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Field holding the payload in each stream entry
+const PAYLOAD_FIELD: &str = "payload";
+
+/// Read position shared between successive subscriptions
+///
+/// Kept outside of the subscription stream so that resubscribing after a
+/// reconnect resumes right after the last delivered entry.
+pub(super) struct StreamState {
+    options: StreamOptions,
+    consumer: String,
+    /// Consumer group of this instance, derived from the configured prefix
+    group: Option<String>,
+    last_id: Mutex<Option<String>>,
+    /// Keys and IDs of the entries handed to the watcher and not fully handled yet
+    unsettled: Mutex<Vec<(String, String)>>,
+    /// Keys and IDs of the entries handled by the watcher, acknowledged by the next read
+    settled: Mutex<Vec<(String, String)>>,
+}
+
+impl StreamState {
+    pub(super) fn new(options: StreamOptions) -> Self {
+        let consumer = options
+            .consumer_name
+            .clone()
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        // A group hands each entry to a single consumer, so instances never share one
+        let group = options
+            .consumer_group
+            .as_ref()
+            .map(|prefix| format!("{}:{}", prefix, consumer));
+        Self {
+            options,
+            consumer,
+            group,
+            last_id: Mutex::new(None),
+            unsettled: Mutex::new(Vec::new()),
+            settled: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Mark every entry of `key` handed to the watcher so far as fully handled
+    ///
+    /// They are acknowledged before the reader fetches or yields more entries.
+    pub(super) fn settle(&self, key: &str) {
+        let mut unsettled = self.unsettled.lock().unwrap();
+        let (handled, pending): (Vec<_>, Vec<_>) = unsettled
+            .drain(..)
+            .partition(|(entry_key, _)| entry_key == key);
+        *unsettled = pending;
+        self.settled.lock().unwrap().extend(handled);
+    }
+
+    /// Take the IDs of the settled entries of `key`
+    fn take_settled(&self, key: &str) -> Vec<String> {
+        let mut settled = self.settled.lock().unwrap();
+        let (ids, others): (Vec<_>, Vec<_>) = settled
+            .drain(..)
+            .partition(|(entry_key, _)| entry_key == key);
+        *settled = others;
+        ids.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// Append `payload` to the stream, trimming it to roughly `max_len` entries
+    pub(super) async fn publish<C>(&self, conn: &mut C, key: &str, payload: Vec<u8>) -> Result<()>
+    where
+        C: ConnectionLike + Send + Sync,
+    {
+        let _: Option<String> = conn
+            .xadd_maxlen(
+                key,
+                StreamMaxlen::Approx(self.options.max_len),
+                "*",
+                &[(PAYLOAD_FIELD, payload)],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Start reading the stream
+    ///
+    /// Resolves once the read position is fixed, so every entry added after
+    /// this call returns is delivered. XREAD BLOCK holds the connection, so
+    /// `conn` must not be shared with other users.
+    pub(super) async fn subscribe<C>(
+        self: &Arc<Self>,
+        mut conn: C,
+        key: &str,
+    ) -> Result<PayloadStream>
+    where
+        C: ConnectionLike + Send + Sync + 'static,
+    {
+        match &self.group {
+            Some(group) => {
+                let created: ::redis::RedisResult<()> =
+                    conn.xgroup_create_mkstream(key, group, "$").await;
+                if let Err(e) = created {
+                    if e.code() != Some("BUSYGROUP") {
+                        return Err(e.into());
+                    }
+                }
+            }
+            None => {
+                let mut last_id = self.last_id.lock().unwrap().clone();
+                if last_id.is_none() {
+                    // First subscription: start after the newest existing entry
+                    let newest: StreamRangeReply = conn.xrevrange_count(key, "+", "-", 1).await?;
+                    let id = newest
+                        .ids
+                        .first()
+                        .map(|entry| entry.id.clone())
+                        .unwrap_or_else(|| "0-0".to_string());
+                    last_id = Some(id);
+                    *self.last_id.lock().unwrap() = last_id;
+                }
+            }
+        }
+
+        let reader = StreamReader {
+            conn,
+            key: key.to_string(),
+            state: self.clone(),
+            buffered: VecDeque::new(),
+            // Entries delivered to this consumer before a restart but never acknowledged
+            read_backlog: true,
+        };
+
+        Ok(Box::pin(futures_util::stream::unfold(
+            reader,
+            StreamReader::next_payload,
+        )))
+    }
+}
+
+struct StreamReader<C> {
+    conn: C,
+    key: String,
+    state: Arc<StreamState>,
+    buffered: VecDeque<(String, Vec<u8>)>,
+    read_backlog: bool,
+}
+
+impl<C: ConnectionLike + Send + Sync> StreamReader<C> {
+    /// Yield the next payload, ending the stream on a connection error
+    async fn next_payload(mut self) -> Option<(Vec<u8>, Self)> {
+        loop {
+            // Entries are only acknowledged once the watcher delivered them
+            // and every callback they triggered has returned
+            let settled = self.state.take_settled(&self.key);
+            if !settled.is_empty() {
+                if let Err(e) = self.ack(&settled).await {
+                    warn!("Failed to acknowledge stream entries: {}", e);
+                    let key = self.key.clone();
+                    self.state
+                        .settled
+                        .lock()
+                        .unwrap()
+                        .extend(settled.into_iter().map(|id| (key.clone(), id)));
+                    return None;
+                }
+            }
+
+            if let Some((id, payload)) = self.buffered.pop_front() {
+                if self.state.group.is_some() {
+                    self.state
+                        .unsettled
+                        .lock()
+                        .unwrap()
+                        .push((self.key.clone(), id));
+                } else {
+                    *self.state.last_id.lock().unwrap() = Some(id);
+                }
+                return Some((payload, self));
+            }
+
+            match self.read().await {
+                Ok(entries) => self.buffer(entries).await,
+                Err(e) => {
+                    warn!("Failed to read stream {}: {}", self.key, e);
+                    return None;
+                }
+            }
+        }
+    }
+
+    async fn read(&mut self) -> ::redis::RedisResult<Vec<StreamId>> {
+        let state = self.state.clone();
+        let block_ms = state.options.block_timeout.as_millis() as usize;
+        let mut opts = StreamReadOptions::default()
+            .count(state.options.batch_size.max(1))
+            .block(block_ms);
+
+        let id = match &state.group {
+            Some(group) => {
+                opts = opts.group(group, &state.consumer);
+                if self.read_backlog {
+                    "0".to_string()
+                } else {
+                    ">".to_string()
+                }
+            }
+            None => state
+                .last_id
+                .lock()
+                .unwrap()
+                .clone()
+                .unwrap_or_else(|| "$".to_string()),
+        };
+
+        let reply: Option<StreamReadReply> =
+            self.conn.xread_options(&[&self.key], &[&id], &opts).await?;
+        let entries: Vec<StreamId> = reply
+            .map(|reply| reply.keys.into_iter().flat_map(|key| key.ids).collect())
+            .unwrap_or_default();
+
+        if self.read_backlog && entries.is_empty() {
+            // Backlog drained, switch to new entries
+            self.read_backlog = false;
+        }
+        Ok(entries)
+    }
+
+    async fn buffer(&mut self, entries: Vec<StreamId>) {
+        for entry in entries {
+            match entry.get::<Vec<u8>>(PAYLOAD_FIELD) {
+                Some(payload) => self.buffered.push_back((entry.id, payload)),
+                None => {
+                    // Trimmed or foreign entry, nothing to deliver
+                    debug!("Skipping stream entry {} without payload", entry.id);
+                    if self.state.group.is_some() {
+                        let _ = self.ack(std::slice::from_ref(&entry.id)).await;
+                    } else {
+                        *self.state.last_id.lock().unwrap() = Some(entry.id);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn ack(&mut self, ids: &[String]) -> ::redis::RedisResult<()> {
+        if let Some(group) = &self.state.group {
+            let _: i64 = self.conn.xack(&self.key, group, ids).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_settled_entries_of_the_key_are_acknowledged() {
+        let state = StreamState::new(StreamOptions::default().with_consumer_group("g".to_string()));
+        state.unsettled.lock().unwrap().extend([
+            ("a".to_string(), "1-0".to_string()),
+            ("b".to_string(), "1-0".to_string()),
+        ]);
+        assert!(state.take_settled("a").is_empty());
+
+        state.settle("a");
+        state
+            .unsettled
+            .lock()
+            .unwrap()
+            .push(("a".to_string(), "2-0".to_string()));
+        assert_eq!(state.take_settled("a"), vec!["1-0"]);
+        assert!(state.take_settled("a").is_empty());
+        assert!(state.take_settled("b").is_empty());
+        assert_eq!(state.unsettled.lock().unwrap().len(), 2);
+    }
+}
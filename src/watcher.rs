@@ -12,16 +12,36 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::batching;
+use crate::chunking::{self, Reassembler};
+use crate::coalescing::{self, Batch, Received};
+use crate::codec::{self, Codec};
+use crate::compression;
+use crate::diagnostics::{debug, error, info, span, trace, warn, Instrument, Payload, Span};
+use crate::encryption;
+use crate::metrics::{Metrics, MetricsSnapshot};
+use crate::options::{
+    BatchingOptions, ChunkingOptions, CompressionOptions, DeliveryMode, EncryptionOptions,
+    HistoryOptions, MessageFormat, PayloadLogging, ReconnectOptions, SigningOptions,
+};
+use crate::queue::{self, Offer, Outgoing, PublishQueue};
+use crate::replay::{self, Fingerprints};
+use crate::signing;
+use crate::transport::{HistoryId, PayloadStream, RedisTransport, Transport};
 use casbin::{EventData, Watcher};
-use redis::{AsyncCommands, Client};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc, Mutex,
 };
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use thiserror::Error;
-use tokio::sync::mpsc;
-use tokio::task::JoinHandle;
+use tokio::sync::{oneshot, watch, Semaphore};
+use tokio::task::{JoinHandle, JoinSet};
 use tokio_stream::StreamExt;
 
 // ========== Error Types ==========
@@ -40,24 +60,90 @@ pub enum WatcherError {
     #[error("Watcher already closed")]
     AlreadyClosed,
 
+    #[error("Casbin error: {0}")]
+    Casbin(#[from] casbin::Error),
+
     #[error("Configuration error: {0}")]
     Configuration(String),
 
     #[error("Runtime error: {0}")]
     Runtime(String),
+
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+
+    #[error("Codec error: {0}")]
+    Codec(String),
+
+    #[error("Compression error: {0}")]
+    Compression(String),
+
+    #[error("Chunking error: {0}")]
+    Chunking(String),
+
+    #[error("Publish queue is full")]
+    QueueFull,
+
+    #[error("Failed to publish message after {attempts} attempts: {source}")]
+    PublishFailed {
+        attempts: u32,
+        #[source]
+        source: Box<WatcherError>,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, WatcherError>;
 
 // Type aliases to reduce complexity
 type UpdateCallback = Box<dyn FnMut(String) + Send + Sync>;
-type CallbackArc = Arc<Mutex<Option<UpdateCallback>>>;
+type MessageCallback = Box<dyn FnMut(Message) + Send + Sync>;
+type BatchCallback = Box<dyn FnMut(Vec<Message>) + Send + Sync>;
+type ErrorCallback = Box<dyn FnMut(WatcherError, String) + Send + Sync>;
+type EventCallback = Box<dyn FnMut(WatcherEvent) + Send + Sync>;
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+type AsyncCallback = Arc<dyn Fn(Message) -> BoxFuture + Send + Sync>;
+type PublishCallback = Box<dyn FnMut(&Message, &Result<PublishReceipt>) + Send + Sync>;
+
+/// Callbacks shared between the watcher and its subscription task
+#[derive(Default)]
+struct Callbacks {
+    update: Mutex<Option<UpdateCallback>>,
+    message: Mutex<Option<MessageCallback>>,
+    batch: Mutex<Option<BatchCallback>>,
+    error: Mutex<Option<ErrorCallback>>,
+    event: Mutex<Option<EventCallback>>,
+    /// The async callback, and whether it must run in message order
+    message_async: Mutex<Option<(AsyncCallback, bool)>>,
+    publish: Mutex<Option<PublishCallback>>,
+}
+
+/// Run `f` with the callback in `slot`, returning whether one was set
+fn with_callback<C: ?Sized>(slot: &Mutex<Option<Box<C>>>, f: impl FnOnce(&mut C)) -> bool {
+    match slot.lock() {
+        Ok(mut guard) => match guard.as_mut() {
+            Some(cb) => {
+                f(cb);
+                true
+            }
+            None => false,
+        },
+        Err(_) => {
+            error!("Failed to acquire callback lock");
+            false
+        }
+    }
+}
 
 // ========== Message Types ==========
 
 /// Message types for communication between watcher instances
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-#[serde(rename_all = "PascalCase")]
+///
+/// Method names this version doesn't know, e.g. from newer instances during a
+/// rolling deploy, decode as [`UpdateType::Unknown`] instead of failing.
+#[derive(Debug, Clone, PartialEq)]
 pub enum UpdateType {
     Update,
     UpdateForAddPolicy,
@@ -68,6 +154,53 @@ pub enum UpdateType {
     UpdateForRemovePolicies,
     UpdateForUpdatePolicy,
     UpdateForUpdatePolicies,
+    /// A method this version doesn't know, with its name
+    Unknown(String),
+}
+
+impl UpdateType {
+    /// Whether the update is handled by reloading the whole policy
+    ///
+    /// Unknown methods are included, since their effect cannot be applied
+    /// incrementally.
+    pub fn is_full_reload(&self) -> bool {
+        matches!(
+            self,
+            UpdateType::Update | UpdateType::UpdateForSavePolicy | UpdateType::Unknown(_)
+        )
+    }
+
+    fn from_name(name: String) -> Self {
+        match name.as_str() {
+            "Update" => UpdateType::Update,
+            "UpdateForAddPolicy" => UpdateType::UpdateForAddPolicy,
+            "UpdateForRemovePolicy" => UpdateType::UpdateForRemovePolicy,
+            "UpdateForRemoveFilteredPolicy" => UpdateType::UpdateForRemoveFilteredPolicy,
+            "UpdateForSavePolicy" => UpdateType::UpdateForSavePolicy,
+            "UpdateForAddPolicies" => UpdateType::UpdateForAddPolicies,
+            "UpdateForRemovePolicies" => UpdateType::UpdateForRemovePolicies,
+            "UpdateForUpdatePolicy" => UpdateType::UpdateForUpdatePolicy,
+            "UpdateForUpdatePolicies" => UpdateType::UpdateForUpdatePolicies,
+            _ => UpdateType::Unknown(name),
+        }
+    }
+}
+
+impl Serialize for UpdateType {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for UpdateType {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer).map(UpdateType::from_name)
+    }
 }
 
 impl std::fmt::Display for UpdateType {
@@ -82,10 +215,55 @@ impl std::fmt::Display for UpdateType {
             UpdateType::UpdateForRemovePolicies => write!(f, "UpdateForRemovePolicies"),
             UpdateType::UpdateForUpdatePolicy => write!(f, "UpdateForUpdatePolicy"),
             UpdateType::UpdateForUpdatePolicies => write!(f, "UpdateForUpdatePolicies"),
+            UpdateType::Unknown(name) => write!(f, "{}", name),
         }
     }
 }
 
+/// Lifecycle events reported through [`RedisWatcher::set_event_callback`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WatcherEvent {
+    /// The subscription was lost, messages published until it is restored may be missed
+    Disconnected,
+    /// The subscription was re-established after `attempts` connection attempts
+    Reconnected { attempts: u32 },
+    /// Reconnection gave up after `attempts` failed attempts
+    ReconnectFailed { attempts: u32 },
+    /// Messages from `sender` were missed: `received` arrived while `expected` was next
+    GapDetected {
+        sender: String,
+        expected: u64,
+        received: u64,
+    },
+    /// `sender` started numbering its messages again, after `last`
+    ///
+    /// Messages it published around its restart may have been missed.
+    SenderRestarted {
+        sender: String,
+        last: u64,
+        received: u64,
+    },
+}
+
+/// Delivery details of a successfully published [`Message`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishReceipt {
+    /// Number of subscribers that received the message, as reported by the transport
+    ///
+    /// Transports that cannot tell, such as Redis Streams, report 0. For a
+    /// chunked message, this is the lowest count of any chunk. Messages
+    /// published in the same batch share the count of the batch.
+    pub receivers: usize,
+    /// Number of publish attempts, including the successful one
+    ///
+    /// For a chunked message, this is the highest count of any chunk. Messages
+    /// published in the same batch share the count of the batch.
+    pub attempts: u32,
+    /// Time from queueing the message until the publish was acknowledged
+    pub latency: Duration,
+}
+
 /// Message structure for Redis pub/sub communication
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
@@ -109,6 +287,16 @@ pub struct Message {
     pub field_index: i32,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub field_values: Vec<String>,
+    /// Position of the message among those published by its sender
+    ///
+    /// Assigned by the publishing watcher, starting at 1. 0 when the sender
+    /// doesn't number its messages, like the Go watcher.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub sequence: u64,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
 }
 
 impl Message {
@@ -124,6 +312,7 @@ impl Message {
             new_rules: Vec::new(),
             field_index: 0,
             field_values: Vec::new(),
+            sequence: 0,
         }
     }
 
@@ -136,6 +325,79 @@ impl Message {
     }
 }
 
+/// Versioned wrapper around a [`Message`]
+///
+/// Published with [`MessageFormat::Envelope`](crate::MessageFormat::Envelope):
+///
+/// ```json
+/// {"Version":1,"Timestamp":1760000000000,"Sender":"node-1","Payload":{"Method":"Update","ID":"node-1"}}
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Envelope {
+    /// Envelope format version, 0 for a bare [`Message`]
+    pub version: u32,
+    /// Milliseconds since the Unix epoch at which the message was published
+    #[serde(default)]
+    pub timestamp: u64,
+    /// Local ID of the publishing instance
+    pub sender: String,
+    pub payload: Message,
+}
+
+impl Envelope {
+    /// Envelope version written by this crate
+    pub const VERSION: u32 = 1;
+
+    /// Wrap `message`, stamped with the current time
+    pub fn new(message: Message) -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as u64);
+        Self {
+            version: Self::VERSION,
+            timestamp,
+            sender: message.id.clone(),
+            payload: message,
+        }
+    }
+
+    /// Version 0 stand-in for a bare `message`
+    pub(crate) fn bare(message: Message) -> Self {
+        Self {
+            version: 0,
+            timestamp: 0,
+            sender: message.id.clone(),
+            payload: message,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Decode an envelope or a bare [`Message`]
+    ///
+    /// Bare messages, as sent by [`MessageFormat::Compat`](crate::MessageFormat::Compat)
+    /// and the Go watcher, are returned with version 0. Envelopes from newer
+    /// versions are decoded as far as they are understood: unknown fields are
+    /// ignored and unknown methods become [`UpdateType::Unknown`].
+    pub fn decode(json: &str) -> Result<Self> {
+        if let Ok(envelope) = serde_json::from_str::<Self>(json) {
+            if envelope.version > Self::VERSION {
+                debug!(
+                    "Decoding envelope version {} with version {} rules",
+                    envelope.version,
+                    Self::VERSION
+                );
+            }
+            return Ok(envelope);
+        }
+
+        Ok(Self::bare(Message::from_json(json)?))
+    }
+}
+
 // ========== Helper Functions ==========
 
 /// Convert EventData to Message for publishing
@@ -180,6 +442,8 @@ fn event_data_to_message(event_data: &EventData, local_id: &str) -> Message {
             if !field_values.is_empty() {
                 message.field_values = field_values[0].clone();
             }
+            // The event carries the removed rules rather than the filter
+            message.old_rules = field_values.clone();
             message
         }
         EventData::SavePolicy(_) => {
@@ -190,99 +454,50 @@ fn event_data_to_message(event_data: &EventData, local_id: &str) -> Message {
     }
 }
 
-// ========== Redis Client Wrapper ==========
-
-/// Wrapper to support both standalone and cluster Redis
-enum RedisClientWrapper {
-    Standalone(Client),
-    // For Cluster mode, we use a single node connection for pubsub
-    // Redis Cluster PubSub messages don't propagate across nodes,
-    // so all instances must connect to the same node for pub/sub
-    ClusterPubSub { pubsub_client: Client },
-}
-
-impl RedisClientWrapper {
-    async fn get_async_pubsub(&self) -> redis::RedisResult<redis::aio::PubSub> {
-        match self {
-            RedisClientWrapper::Standalone(client) => client.get_async_pubsub().await,
-            RedisClientWrapper::ClusterPubSub { pubsub_client } => {
-                // Use the dedicated pubsub client for cluster mode
-                pubsub_client.get_async_pubsub().await
-            }
-        }
+/// Apply the delivery mode from the options to a Redis transport
+fn configure_transport(
+    transport: RedisTransport,
+    options: &crate::WatcherOptions,
+) -> RedisTransport {
+    let mut mode = options.delivery_mode.clone();
+    if let DeliveryMode::Streams(stream_options) = &mut mode {
+        stream_options
+            .consumer_name
+            .get_or_insert_with(|| options.local_id.clone());
     }
+    transport.with_delivery_mode(mode)
+}
 
-    async fn publish_message(&self, channel: &str, payload: String) -> redis::RedisResult<()> {
-        match self {
-            RedisClientWrapper::Standalone(client) => {
-                let mut conn = client.get_multiplexed_async_connection().await?;
-                let _: i32 = conn.publish(channel, payload).await?;
-                Ok(())
-            }
-            RedisClientWrapper::ClusterPubSub { pubsub_client } => {
-                // For Redis Cluster, we need to publish to the same node where PubSub is subscribed
-                // because PubSub messages don't propagate across cluster nodes
-                // Use the pubsub_client (single node) for both publishing and subscribing
-                let mut conn = pubsub_client.get_multiplexed_async_connection().await?;
-                let _: i32 = conn.publish(channel, payload).await?;
-                log::debug!("Published to cluster node via pubsub_client");
-                Ok(())
-            }
-        }
-    }
+/// How a received message follows the previous one from its sender
+enum Sequence {
+    InOrder,
+    /// Messages from the sender may have been missed
+    Gap,
+    /// Possibly an old signed message sent again
+    Stale,
 }
 
 // ========== Redis Watcher Implementation ==========
 
-pub struct RedisWatcher {
-    client: Arc<RedisClientWrapper>,
+pub struct RedisWatcher<T: Transport = RedisTransport> {
+    transport: Arc<T>,
     options: crate::WatcherOptions,
-    callback: CallbackArc,
-    publish_tx: mpsc::UnboundedSender<Message>,
+    callbacks: Arc<Callbacks>,
+    metrics: Arc<Metrics>,
+    publish_queue: Arc<PublishQueue>,
     publish_task: Arc<Mutex<Option<JoinHandle<()>>>>,
     subscription_task: Arc<Mutex<Option<JoinHandle<()>>>>,
+    /// Async callbacks spawned with `callback_concurrency` above 1
+    callback_tasks: Arc<Mutex<JoinSet<()>>>,
     is_closed: Arc<AtomicBool>,
-    subscription_ready: Arc<tokio::sync::Notify>,
+    subscription_ready: Arc<watch::Sender<bool>>,
 }
 
-impl RedisWatcher {
+impl RedisWatcher<RedisTransport> {
     /// Create a new Redis watcher for standalone Redis
     pub fn new(redis_url: &str, options: crate::WatcherOptions) -> Result<Self> {
-        let client = Arc::new(RedisClientWrapper::Standalone(Client::open(redis_url)?));
-
-        // Create publish channel
-        let (publish_tx, publish_rx) = mpsc::unbounded_channel::<Message>();
-
-        let is_closed = Arc::new(AtomicBool::new(false));
-        let subscription_ready = Arc::new(tokio::sync::Notify::new());
-
-        // Spawn publish task
-        let publish_task = {
-            let client = client.clone();
-            let channel = options.channel.clone();
-            let is_closed = is_closed.clone();
-
-            tokio::spawn(async move {
-                Self::publish_worker(publish_rx, client, channel, is_closed).await
-            })
-        };
-
-        let watcher = Self {
-            client,
-            options,
-            callback: Arc::new(Mutex::new(None)),
-            publish_tx,
-            publish_task: Arc::new(Mutex::new(Some(publish_task))),
-            subscription_task: Arc::new(Mutex::new(None)),
-            is_closed,
-            subscription_ready,
-        };
-
-        // Start subscription immediately like Go version does
-        // This ensures the watcher is ready to receive messages before any publishes happen
-        watcher.start_subscription()?;
-
-        Ok(watcher)
+        let transport = RedisTransport::standalone(redis_url)?;
+        Self::with_transport(configure_transport(transport, &options), options)
     }
 
     /// Create a new Redis watcher for Redis Cluster
@@ -290,58 +505,109 @@ impl RedisWatcher {
     /// Note: Redis Cluster PubSub messages don't propagate between nodes.
     /// All instances MUST connect to the SAME node for pub/sub to work.
     /// This method uses the first URL as the fixed PubSub node.
+    /// On Redis 7+, prefer [`RedisWatcher::new_sharded_cluster`].
     ///
     /// # Arguments
     /// * `cluster_urls` - Comma-separated Redis URLs (first URL used for PubSub)
     /// * `options` - Watcher configuration options
     pub fn new_cluster(cluster_urls: &str, options: crate::WatcherOptions) -> Result<Self> {
-        // Parse cluster URLs
-        let urls: Vec<&str> = cluster_urls.split(',').map(|s| s.trim()).collect();
-        if urls.is_empty() {
-            return Err(WatcherError::Configuration(
-                "No cluster URLs provided".to_string(),
-            ));
-        }
-
-        // For Redis Cluster PubSub: use the first node for both publish and subscribe
-        // This ensures messages are sent and received on the same node
-        // since PubSub messages don't propagate across cluster nodes
-        let pubsub_url = urls[0];
-        let pubsub_client = Client::open(pubsub_url).map_err(|e| {
-            WatcherError::Configuration(format!("Failed to create pubsub client: {}", e))
-        })?;
+        let transport = RedisTransport::cluster(cluster_urls)?;
+        Self::with_transport(configure_transport(transport, &options), options)
+    }
 
-        log::warn!(
-            "Redis Cluster PubSub using fixed node: {} - ALL instances MUST use the SAME node!",
-            pubsub_url
-        );
+    /// Create a new Redis watcher for Redis Cluster 7+ using sharded pub/sub
+    ///
+    /// SPUBLISH/SSUBSCRIBE route the channel to the node owning its hash slot,
+    /// so unlike [`RedisWatcher::new_cluster`] instances may connect through
+    /// any node, and the watcher keeps working when that node fails over.
+    ///
+    /// # Arguments
+    /// * `cluster_urls` - Comma-separated Redis URLs used to discover the cluster
+    /// * `options` - Watcher configuration options
+    pub fn new_sharded_cluster(cluster_urls: &str, options: crate::WatcherOptions) -> Result<Self> {
+        let transport = RedisTransport::sharded_cluster(cluster_urls)?;
+        Self::with_transport(configure_transport(transport, &options), options)
+    }
 
-        let client = Arc::new(RedisClientWrapper::ClusterPubSub { pubsub_client });
+    /// Create a new Redis watcher for a master monitored by Redis Sentinel
+    ///
+    /// The master is discovered through the sentinels. On `+switch-master`,
+    /// both the publish and the subscribe connections move to the new master.
+    ///
+    /// # Arguments
+    /// * `sentinels` - Comma-separated Sentinel URLs
+    /// * `master_name` - Name of the master monitored by the sentinels
+    /// * `options` - Watcher configuration options
+    pub fn new_sentinel(
+        sentinels: &str,
+        master_name: &str,
+        options: crate::WatcherOptions,
+    ) -> Result<Self> {
+        let transport = RedisTransport::sentinel(sentinels, master_name)?;
+        Self::with_transport(configure_transport(transport, &options), options)
+    }
+}
 
-        // Create publish channel
-        let (publish_tx, publish_rx) = mpsc::unbounded_channel::<Message>();
+impl<T: Transport> RedisWatcher<T> {
+    /// Create a new watcher on top of a custom [`Transport`]
+    pub fn with_transport(transport: T, options: crate::WatcherOptions) -> Result<Self> {
+        if let Some(signing) = &options.signing {
+            signing::validate(signing)?;
+        }
+        if let Some(compression) = &options.compression {
+            compression::validate(compression)?;
+        }
+        if let Some(chunking) = &options.chunking {
+            chunking::validate(chunking)?;
+        }
+        if let Some(batching) = &options.batching {
+            batching::validate(batching)?;
+        }
+        if let Some(coalescing) = &options.coalescing {
+            coalescing::validate(coalescing)?;
+        }
+        if let Some(publish_queue) = &options.publish_queue {
+            queue::validate(publish_queue)?;
+        }
+        let transport = Arc::new(transport);
 
         let is_closed = Arc::new(AtomicBool::new(false));
-        let subscription_ready = Arc::new(tokio::sync::Notify::new());
+        let subscription_ready = Arc::new(watch::Sender::new(false));
+        let callbacks = Arc::new(Callbacks::default());
+        let metrics = Arc::new(Metrics::new(&options.channel));
 
         // Spawn publish task
-        let publish_task = {
-            let client = client.clone();
-            let channel = options.channel.clone();
-            let is_closed = is_closed.clone();
-
-            tokio::spawn(async move {
-                Self::publish_worker(publish_rx, client, channel, is_closed).await
-            })
+        let publish_queue = Arc::new(PublishQueue::new(
+            options.publish_queue,
+            options.local_id.clone(),
+        ));
+        let publisher = Publisher {
+            transport: transport.clone(),
+            channel: options.channel.clone(),
+            queue: publish_queue.clone(),
+            callbacks: callbacks.clone(),
+            metrics: metrics.clone(),
+            payload_logging: options.payload_logging,
+            message_format: options.message_format,
+            codec: options.codec.clone(),
+            compression: options.compression.clone(),
+            chunking: options.chunking.clone(),
+            batching: options.batching.clone(),
+            history: options.history.clone(),
+            signing: options.signing.clone(),
+            encryption: options.encryption.clone(),
         };
+        let publish_task = tokio::spawn(publisher.publish_worker());
 
         let watcher = Self {
-            client,
+            transport,
             options,
-            callback: Arc::new(Mutex::new(None)),
-            publish_tx,
+            callbacks,
+            metrics,
+            publish_queue,
             publish_task: Arc::new(Mutex::new(Some(publish_task))),
             subscription_task: Arc::new(Mutex::new(None)),
+            callback_tasks: Arc::new(Mutex::new(JoinSet::new())),
             is_closed,
             subscription_ready,
         };
@@ -353,62 +619,6 @@ impl RedisWatcher {
         Ok(watcher)
     }
 
-    /// Background worker for publishing messages
-    async fn publish_worker(
-        mut rx: mpsc::UnboundedReceiver<Message>,
-        client: Arc<RedisClientWrapper>,
-        channel: String,
-        is_closed: Arc<AtomicBool>,
-    ) {
-        while let Some(message) = rx.recv().await {
-            if is_closed.load(Ordering::Relaxed) {
-                break;
-            }
-
-            if let Ok(payload) = message.to_json() {
-                eprintln!(
-                    "[RedisWatcher] Publishing message to channel {}: {}",
-                    channel, payload
-                );
-
-                // Retry publishing with exponential backoff
-                let mut retry_count = 0;
-                loop {
-                    match client.publish_message(&channel, payload.clone()).await {
-                        Ok(_) => {
-                            eprintln!(
-                                "[RedisWatcher] Successfully published message to channel: {}",
-                                channel
-                            );
-                            break;
-                        }
-                        Err(e) => {
-                            retry_count += 1;
-                            eprintln!(
-                                "[RedisWatcher] Failed to publish message (attempt {}): {}",
-                                retry_count, e
-                            );
-                            if retry_count >= 3 {
-                                eprintln!(
-                                    "[RedisWatcher] Failed to publish message after {} attempts: {}",
-                                    retry_count,
-                                    e
-                                );
-                                break;
-                            }
-                            tokio::time::sleep(tokio::time::Duration::from_millis(
-                                100 * retry_count,
-                            ))
-                            .await;
-                        }
-                    }
-                }
-            } else {
-                eprintln!("[RedisWatcher] Failed to serialize message to JSON");
-            }
-        }
-    }
-
     /// Wait for subscription to be ready (similar to Go's WaitGroup.Wait())
     ///
     /// This ensures that the watcher is fully subscribed before publishing messages.
@@ -416,20 +626,180 @@ impl RedisWatcher {
     pub async fn wait_for_ready(&self) {
         // Wait with timeout
         let timeout = tokio::time::Duration::from_secs(5);
-        let _ = tokio::time::timeout(timeout, self.subscription_ready.notified()).await;
+        let mut ready_rx = self.subscription_ready.subscribe();
+        let _ = tokio::time::timeout(timeout, ready_rx.wait_for(|ready| *ready)).await;
+    }
+
+    /// Snapshot of the counters and latency histograms collected so far
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
     }
 
-    /// Publish message to Redis channel
+    /// Publish `message` and wait until the transport acknowledged it
+    ///
+    /// The message goes through the same queue as updates from
+    /// [`Watcher::update`], so ordering between both is preserved. Once the
+    /// retries are exhausted, [`WatcherError::PublishFailed`] is returned.
+    ///
+    /// When the publish queue is full, [`OverflowPolicy::Block`](crate::OverflowPolicy::Block)
+    /// waits for room, and a message dropped by another policy is reported as
+    /// [`WatcherError::QueueFull`].
+    pub async fn publish(&self, message: &Message) -> Result<PublishReceipt> {
+        if self.is_closed.load(Ordering::Relaxed) {
+            return Err(WatcherError::AlreadyClosed);
+        }
+
+        let (ack_tx, ack_rx) = oneshot::channel();
+        let outgoing = Outgoing::new(message.clone(), Some(ack_tx));
+        if let Some(mut waiting) = self.offer(outgoing)? {
+            self.metrics.queue_overflow();
+            loop {
+                self.publish_queue.wait_for_room().await;
+                match self.offer(waiting)? {
+                    Some(still_waiting) => waiting = still_waiting,
+                    None => break,
+                }
+            }
+        }
+        // The sender is only dropped unanswered when close() gave up on the message
+        ack_rx.await.map_err(|_| WatcherError::AlreadyClosed)?
+    }
+
+    /// Queue message for publishing to Redis channel
+    ///
+    /// With [`OverflowPolicy::Block`](crate::OverflowPolicy::Block), a full
+    /// queue blocks the calling thread. That would stall a current-thread
+    /// runtime, whose only thread also runs the publish task, so the message is
+    /// rejected there instead.
     fn publish_message(&self, message: &Message) -> Result<()> {
         if self.is_closed.load(Ordering::Relaxed) {
             return Err(WatcherError::AlreadyClosed);
         }
 
-        self.publish_tx
-            .send(message.clone())
-            .map_err(|_| WatcherError::Runtime("Publish channel closed".to_string()))?;
+        let outgoing = Outgoing::new(message.clone(), None);
+        let Some(mut waiting) = self.offer(outgoing)? else {
+            return Ok(());
+        };
+        self.metrics.queue_overflow();
+        let current_thread = tokio::runtime::Handle::try_current().is_ok_and(|handle| {
+            handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::CurrentThread
+        });
+        if current_thread {
+            self.report_dropped(waiting);
+            return Err(WatcherError::QueueFull);
+        }
 
-        Ok(())
+        let wait = || loop {
+            self.publish_queue.wait_for_room_blocking();
+            match self.offer(waiting)? {
+                Some(still_waiting) => waiting = still_waiting,
+                None => return Ok(()),
+            }
+        };
+        if tokio::runtime::Handle::try_current().is_ok() {
+            // Let the runtime move other tasks off this worker thread meanwhile
+            tokio::task::block_in_place(wait)
+        } else {
+            wait()
+        }
+    }
+
+    /// Offer `outgoing` to the publish queue
+    ///
+    /// Returns the message back when the queue is full and the overflow
+    /// policy is to wait for room.
+    fn offer(&self, outgoing: Outgoing) -> Result<Option<Outgoing>> {
+        match self.publish_queue.offer(outgoing)? {
+            Offer::Queued => Ok(None),
+            Offer::Overflowed(dropped) => {
+                self.metrics.queue_overflow();
+                if dropped.is_empty() {
+                    warn!("Publish queue is full, collapsed queued messages into a full reload");
+                }
+                for outgoing in dropped {
+                    warn!(
+                        "Publish queue is full, dropped oldest {} message",
+                        outgoing.message.method
+                    );
+                    self.report_dropped(outgoing);
+                }
+                Ok(None)
+            }
+            Offer::Rejected(outgoing) => {
+                self.metrics.queue_overflow();
+                self.report_dropped(outgoing);
+                Err(WatcherError::QueueFull)
+            }
+            Offer::Full(outgoing) => Ok(Some(outgoing)),
+        }
+    }
+
+    /// Report a message dropped from the publish queue as never published
+    fn report_dropped(&self, outgoing: Outgoing) {
+        let result = Err(WatcherError::QueueFull);
+        with_callback(&self.callbacks.publish, |cb| cb(&outgoing.message, &result));
+        outgoing.settle(result);
+    }
+
+    /// Close the watcher, flushing pending publishes
+    ///
+    /// New updates are rejected with [`WatcherError::AlreadyClosed`]. Messages
+    /// already queued are published until
+    /// [`WatcherOptions::close_timeout`](crate::WatcherOptions::close_timeout)
+    /// expires, then the subscription is shut down. Async callbacks still
+    /// running are awaited within the same timeout, then aborted.
+    ///
+    /// Returns the number of queued messages that could not be published.
+    /// Calling `close` again returns [`WatcherError::AlreadyClosed`].
+    pub async fn close(&self) -> Result<usize> {
+        if self.is_closed.swap(true, Ordering::SeqCst) {
+            return Err(WatcherError::AlreadyClosed);
+        }
+
+        // A timeout too long to represent means waiting for as long as it takes
+        let deadline = tokio::time::Instant::now().checked_add(self.options.close_timeout);
+
+        self.publish_queue.close();
+        let publish_task = self.publish_task.lock().unwrap().take();
+        if let Some(mut handle) = publish_task {
+            if until(deadline, &mut handle).await.is_none() {
+                handle.abort();
+                self.publish_queue.clear();
+                // Whatever is still queued or in flight will never be sent
+                let pending = self.publish_queue.pending.swap(0, Ordering::SeqCst);
+                self.publish_queue
+                    .dropped
+                    .fetch_add(pending, Ordering::SeqCst);
+            }
+        }
+
+        let subscription_task = self.subscription_task.lock().unwrap().take();
+        if let Some(mut handle) = subscription_task {
+            if until(deadline, &mut handle).await.is_none() {
+                handle.abort();
+            }
+        }
+        self.subscription_ready.send_replace(false);
+
+        // No callback is spawned once the subscription has stopped
+        let mut callback_tasks = std::mem::take(&mut *self.callback_tasks.lock().unwrap());
+        let drained = until(deadline, async {
+            while callback_tasks.join_next().await.is_some() {}
+        })
+        .await;
+        if drained.is_none() {
+            warn!(
+                "Aborting {} async callbacks still running at close",
+                callback_tasks.len()
+            );
+            callback_tasks.abort_all();
+        }
+
+        let dropped = self.publish_queue.dropped.load(Ordering::SeqCst);
+        if dropped > 0 {
+            warn!("Closed watcher with {} unpublished messages", dropped);
+        }
+        Ok(dropped)
     }
 
     /// Start subscription to Redis channel
@@ -438,168 +808,1050 @@ impl RedisWatcher {
             return Err(WatcherError::AlreadyClosed);
         }
 
-        let callback = self.callback.clone();
-        let channel = self.options.channel.clone();
-        let local_id = self.options.local_id.clone();
-        let ignore_self = self.options.ignore_self;
-        let is_closed = self.is_closed.clone();
-        let client = self.client.clone();
-        let subscription_ready = self.subscription_ready.clone();
-
-        let handle = tokio::spawn(async move {
-            Self::subscription_worker(
-                client,
-                channel,
-                local_id,
-                ignore_self,
-                is_closed,
-                callback,
-                subscription_ready,
-            )
-            .await
-        });
+        let history_len = self
+            .options
+            .history
+            .as_ref()
+            .map_or(1, |history| history.max_len);
+        let subscriber = Subscriber {
+            transport: self.transport.clone(),
+            channel: self.options.channel.clone(),
+            local_id: self.options.local_id.clone(),
+            ignore_self: self.options.ignore_self,
+            payload_logging: self.options.payload_logging,
+            codec: self.options.codec.clone(),
+            reassembler: Mutex::new(Reassembler::new(self.options.chunking.as_ref())),
+            reload_on_gap: self.options.reload_on_gap,
+            sequences: Mutex::new(HashMap::new()),
+            history_cursor: Mutex::new(None),
+            live: Mutex::new(Fingerprints::new(history_len)),
+            replayed: Mutex::new(Fingerprints::new(history_len)),
+            history: self.options.history.clone(),
+            batch: self
+                .options
+                .coalescing
+                .clone()
+                .map(|coalescing| Mutex::new(Batch::new(coalescing))),
+            signing: self.options.signing.clone(),
+            encryption: self.options.encryption.clone(),
+            metrics: self.metrics.clone(),
+            reconnect: self.options.reconnect.clone(),
+            callback_concurrency: self.options.callback_concurrency,
+            callback_permits: Arc::new(Semaphore::new(self.options.callback_concurrency.max(1))),
+            callback_tasks: self.callback_tasks.clone(),
+            is_closed: self.is_closed.clone(),
+            callbacks: self.callbacks.clone(),
+            subscription_ready: self.subscription_ready.clone(),
+        };
+
+        let handle = tokio::spawn(subscriber.subscription_worker());
 
         *self.subscription_task.lock().unwrap() = Some(handle);
         Ok(())
     }
 
-    /// Background worker for subscription
-    async fn subscription_worker(
-        client: Arc<RedisClientWrapper>,
-        channel: String,
-        local_id: String,
-        ignore_self: bool,
-        is_closed: Arc<AtomicBool>,
-        callback: CallbackArc,
-        subscription_ready: Arc<tokio::sync::Notify>,
-    ) {
-        let result = async {
-            // Retry connection with backoff
-            let mut retry_count = 0;
-            let mut pubsub = loop {
-                if is_closed.load(Ordering::Relaxed) {
-                    return Ok(());
+    /// Set a callback for watcher lifecycle events
+    ///
+    /// On [`WatcherEvent::Reconnected`], messages published while the
+    /// subscription was down may have been missed, so the application should
+    /// reload the full policy.
+    pub fn set_event_callback(&mut self, cb: Box<dyn FnMut(WatcherEvent) + Send + Sync>) {
+        *self.callbacks.event.lock().unwrap() = Some(cb);
+    }
+
+    /// Set a callback receiving the outcome of every published message
+    ///
+    /// Updates sent through [`Watcher::update`] are fire-and-forget; this
+    /// callback tells whether they actually reached the channel, and how many
+    /// subscribers received them.
+    pub fn set_publish_callback(&mut self, cb: PublishCallback) {
+        *self.callbacks.publish.lock().unwrap() = Some(cb);
+    }
+
+    /// Set a callback receiving each update as a parsed [`Message`]
+    ///
+    /// This runs in addition to the raw string callback installed through
+    /// [`Watcher::set_update_callback`], so consumers don't need to call
+    /// [`Message::from_json`] themselves.
+    pub fn set_message_callback(&mut self, cb: Box<dyn FnMut(Message) + Send + Sync>) {
+        *self.callbacks.message.lock().unwrap() = Some(cb);
+    }
+
+    /// Set a callback receiving the updates of each delivery at once
+    ///
+    /// With [`WatcherOptions::coalescing`](crate::WatcherOptions::coalescing),
+    /// the callback runs once per burst, with every message of it or with a
+    /// single [`UpdateType::Update`] if the burst contained a full reload.
+    /// The other callbacks then only get an `Update` standing in for the
+    /// burst, so this is the callback to use for applying a burst
+    /// incrementally. Without coalescing, each message is delivered on its own.
+    pub fn set_batch_callback(&mut self, cb: Box<dyn FnMut(Vec<Message>) + Send + Sync>) {
+        *self.callbacks.batch.lock().unwrap() = Some(cb);
+    }
+
+    /// Set a callback for received payloads that cannot be parsed into a [`Message`]
+    ///
+    /// The callback gets the parse error and the raw payload.
+    pub fn set_error_callback(&mut self, cb: Box<dyn FnMut(WatcherError, String) + Send + Sync>) {
+        *self.callbacks.error.lock().unwrap() = Some(cb);
+    }
+
+    /// Set an async callback receiving each update as a parsed [`Message`]
+    ///
+    /// The returned future is awaited by the subscription task, so reload
+    /// logic can use async adapters directly:
+    ///
+    /// ```rust,no_run
+    /// # use redis_watcher::{RedisWatcher, WatcherOptions};
+    /// # use casbin::prelude::*;
+    /// # use std::sync::Arc;
+    /// # use tokio::sync::RwLock;
+    /// # async fn example(enforcer: Arc<RwLock<Enforcer>>) -> redis_watcher::Result<()> {
+    /// let mut watcher = RedisWatcher::new("redis://127.0.0.1:6379", WatcherOptions::default())?;
+    /// watcher.set_async_callback(move |_msg| {
+    ///     let enforcer = enforcer.clone();
+    ///     async move {
+    ///         let _ = enforcer.write().await.load_policy().await;
+    ///     }
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// With [`WatcherOptions::callback_concurrency`](crate::WatcherOptions::callback_concurrency)
+    /// set to 1 (the default), invocations run one at a time in message order.
+    /// Higher values spawn up to that many invocations concurrently.
+    pub fn set_async_callback<F, Fut>(&mut self, cb: F)
+    where
+        F: Fn(Message) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.install_async_callback(cb, false);
+    }
+
+    /// Set an async callback that always runs one invocation at a time in
+    /// message order, whatever the callback concurrency
+    pub(crate) fn set_ordered_async_callback<F, Fut>(&mut self, cb: F)
+    where
+        F: Fn(Message) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.install_async_callback(cb, true);
+    }
+
+    fn install_async_callback<F, Fut>(&mut self, cb: F, in_order: bool)
+    where
+        F: Fn(Message) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let cb: AsyncCallback = Arc::new(move |message| Box::pin(cb(message)) as BoxFuture);
+        *self.callbacks.message_async.lock().unwrap() = Some((cb, in_order));
+    }
+}
+
+/// Await `future` until `deadline`, or for as long as it takes without one
+///
+/// Returns `None` if the deadline passed first.
+async fn until<F: Future>(deadline: Option<tokio::time::Instant>, future: F) -> Option<F::Output> {
+    match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline, future).await.ok(),
+        None => Some(future.await),
+    }
+}
+
+// ========== Publish Worker ==========
+
+/// Wait up to the linger window for more queued messages to join `batch`
+async fn linger(batching: &BatchingOptions, queue: &PublishQueue, batch: &mut Vec<Outgoing>) {
+    // A window too long to represent only closes once the batch is full
+    let deadline = tokio::time::Instant::now().checked_add(batching.linger);
+    while batch.len() < batching.max_batch {
+        match until(deadline, queue.next()).await {
+            Some(Some(outgoing)) => batch.push(outgoing),
+            // The window closed, or the queue was closed and drained
+            _ => break,
+        }
+    }
+}
+
+/// Error reported for each of several messages that failed to publish together
+pub(crate) fn copied_failure(e: &WatcherError) -> WatcherError {
+    match e {
+        WatcherError::PublishFailed { attempts, source } => WatcherError::PublishFailed {
+            attempts: *attempts,
+            source: Box::new(WatcherError::Runtime(source.to_string())),
+        },
+        e => WatcherError::Runtime(e.to_string()),
+    }
+}
+
+/// State owned by the background publish task
+struct Publisher<T: Transport> {
+    transport: Arc<T>,
+    channel: String,
+    queue: Arc<PublishQueue>,
+    callbacks: Arc<Callbacks>,
+    metrics: Arc<Metrics>,
+    payload_logging: PayloadLogging,
+    message_format: MessageFormat,
+    codec: Arc<dyn Codec>,
+    compression: Option<CompressionOptions>,
+    chunking: Option<ChunkingOptions>,
+    batching: Option<BatchingOptions>,
+    history: Option<HistoryOptions>,
+    signing: Option<SigningOptions>,
+    encryption: Option<EncryptionOptions>,
+}
+
+impl<T: Transport> Publisher<T> {
+    /// Background worker for publishing messages
+    ///
+    /// Once the queue is closed, the worker exits after publishing the
+    /// messages already queued.
+    ///
+    /// Messages are numbered in the order they are published. Messages that
+    /// fail to publish keep their number, so receivers see the gap.
+    async fn publish_worker(self) {
+        let mut sequence = 0;
+        while let Some(outgoing) = self.queue.next().await {
+            let mut batch = vec![outgoing];
+            if let Some(batching) = &self.batching {
+                linger(batching, &self.queue, &mut batch).await;
+            }
+            for outgoing in &mut batch {
+                sequence += 1;
+                outgoing.message.sequence = sequence;
+            }
+
+            let results = if let [outgoing] = batch.as_slice() {
+                let span =
+                    span!("publish", channel = %self.channel, method = %outgoing.message.method);
+                vec![
+                    self.publish(&outgoing.message, outgoing.queued_at)
+                        .instrument(span)
+                        .await,
+                ]
+            } else {
+                let span = span!("publish_batch", channel = %self.channel, messages = batch.len());
+                self.publish_batch(&batch).instrument(span).await
+            };
+
+            for (outgoing, result) in batch.into_iter().zip(results) {
+                self.queue.pending.fetch_sub(1, Ordering::SeqCst);
+                if result.is_err() && self.queue.is_closing() {
+                    self.queue.dropped.fetch_add(1, Ordering::SeqCst);
                 }
 
-                match client.get_async_pubsub().await {
-                    Ok(p) => break p,
-                    Err(e) => {
-                        retry_count += 1;
-                        log::warn!(
-                            "Failed to get async pubsub (attempt {}): {}",
-                            retry_count,
-                            e
+                with_callback(&self.callbacks.publish, |cb| cb(&outgoing.message, &result));
+                outgoing.settle(result);
+            }
+        }
+    }
+
+    /// Publish a single message, retrying failures
+    ///
+    /// Messages larger than the chunk size are published as several frames.
+    async fn publish(&self, message: &Message, queued_at: Instant) -> Result<PublishReceipt> {
+        let bytes = self.prepare(message)?;
+        let description = format!("{} message", message.method);
+        let (receivers, attempts) = self.send(&description, bytes).await?;
+
+        let latency = queued_at.elapsed();
+        self.metrics.published(latency);
+        debug!(
+            "Published message to channel {} ({} receivers)",
+            self.channel, receivers
+        );
+        Ok(PublishReceipt {
+            receivers,
+            attempts,
+            latency,
+        })
+    }
+
+    /// Publish several messages as one batch, returning the outcome of each
+    ///
+    /// Messages that cannot be serialized fail on their own; the others share
+    /// the outcome of the batch.
+    async fn publish_batch(&self, batch: &[Outgoing]) -> Vec<Result<PublishReceipt>> {
+        let mut payloads = Vec::with_capacity(batch.len());
+        let mut failures = Vec::with_capacity(batch.len());
+        for outgoing in batch {
+            match self.prepare(&outgoing.message) {
+                Ok(bytes) => {
+                    payloads.push(bytes);
+                    failures.push(None);
+                }
+                Err(e) => failures.push(Some(e)),
+            }
+        }
+        if payloads.is_empty() {
+            return failures.into_iter().flatten().map(Err).collect();
+        }
+
+        let description = format!("batch of {} messages", payloads.len());
+        let sent = self.send(&description, batching::frame(&payloads)).await;
+        if sent.is_ok() {
+            self.metrics.batch();
+            debug!("Published {} to channel {}", description, self.channel);
+        }
+
+        batch
+            .iter()
+            .zip(failures)
+            .map(|(outgoing, failure)| match (failure, &sent) {
+                (Some(e), _) => Err(e),
+                (None, Ok((receivers, attempts))) => {
+                    let latency = outgoing.queued_at.elapsed();
+                    self.metrics.published(latency);
+                    Ok(PublishReceipt {
+                        receivers: *receivers,
+                        attempts: *attempts,
+                        latency,
+                    })
+                }
+                (None, Err(e)) => Err(copied_failure(e)),
+            })
+            .collect()
+    }
+
+    /// Serialize and seal a message
+    fn prepare(&self, message: &Message) -> Result<Vec<u8>> {
+        let envelope = match self.message_format {
+            MessageFormat::Compat => Envelope::bare(message.clone()),
+            MessageFormat::Envelope => Envelope::new(message.clone()),
+        };
+        let payload = codec::encode(self.codec.as_ref(), &envelope).inspect_err(|e| {
+            error!("Failed to serialize {} message: {}", message.method, e);
+        })?;
+        debug!(
+            "Publishing message to channel {}: {}",
+            self.channel,
+            Payload::new(self.payload_logging, &payload, Some(message))
+        );
+
+        self.seal(message, payload).inspect_err(|e| {
+            error!("Failed to seal {} message: {}", message.method, e);
+        })
+    }
+
+    /// Publish a sealed payload, in chunks if needed, and record it in the history
+    ///
+    /// Returns the smallest number of receivers of any chunk and the most
+    /// attempts any chunk took.
+    async fn send(&self, description: &str, bytes: Vec<u8>) -> Result<(usize, u32)> {
+        // The history keeps the whole payload, even when it is sent in chunks
+        let entry = self.history.as_ref().map(|_| replay::frame(&bytes));
+        let frames = match &self.chunking {
+            Some(chunking) => chunking::split(chunking, bytes),
+            None => vec![bytes],
+        };
+        if frames.len() > 1 {
+            debug!(
+                "Publishing {} to channel {} in {} chunks",
+                description,
+                self.channel,
+                frames.len()
+            );
+        }
+
+        let mut receivers = usize::MAX;
+        let mut attempts = 0;
+        for frame in frames {
+            let (frame_receivers, frame_attempts) = self.publish_frame(description, frame).await?;
+            receivers = receivers.min(frame_receivers);
+            attempts = attempts.max(frame_attempts);
+        }
+        if let (Some(history), Some(entry)) = (&self.history, entry) {
+            self.append_history(history, entry).await;
+        }
+        Ok((receivers, attempts))
+    }
+
+    /// Publish a single frame, retrying failures
+    ///
+    /// Returns the number of receivers and of attempts made.
+    async fn publish_frame(&self, description: &str, frame: Vec<u8>) -> Result<(usize, u32)> {
+        // Retry publishing with exponential backoff
+        let mut retry_count = 0;
+        loop {
+            match self.transport.publish(&self.channel, frame.clone()).await {
+                Ok(receivers) => return Ok((receivers, retry_count + 1)),
+                Err(e) => {
+                    retry_count += 1;
+                    warn!(
+                        "Failed to publish message to channel {} (attempt {}): {}",
+                        self.channel, retry_count, e
+                    );
+                    if retry_count >= 3 {
+                        self.metrics.publish_failure();
+                        error!(
+                            "Giving up on publishing {} to channel {} after {} attempts: {}",
+                            description, self.channel, retry_count, e
                         );
-                        if retry_count > 5 {
-                            return Err(e);
-                        }
-                        tokio::time::sleep(tokio::time::Duration::from_millis(1000 * retry_count))
-                            .await;
+                        return Err(WatcherError::PublishFailed {
+                            attempts: retry_count,
+                            source: Box::new(e),
+                        });
                     }
+                    self.metrics.publish_retry();
+                    tokio::time::sleep(Duration::from_millis(100 * u64::from(retry_count))).await;
                 }
-            };
+            }
+        }
+    }
 
-            // Subscribe with retry
-            let mut subscribe_retry = 0;
-            loop {
-                if is_closed.load(Ordering::Relaxed) {
-                    return Ok(());
+    /// Append a published message to the replay history
+    ///
+    /// The message has already reached the channel, so failures are only logged.
+    async fn append_history(&self, history: &HistoryOptions, entry: Vec<u8>) {
+        if let Err(e) = self
+            .transport
+            .append_history(&self.channel, entry, history.max_len, history.ttl)
+            .await
+        {
+            warn!(
+                "Failed to append message to the history of channel {}: {}",
+                self.channel, e
+            );
+        }
+    }
+
+    /// Apply the compression, encryption and signing layers to a serialized message
+    fn seal(&self, message: &Message, payload: Vec<u8>) -> Result<Vec<u8>> {
+        let payload = match &self.compression {
+            Some(compression) => match compression::compress(compression, &payload)? {
+                Some(compressed) => {
+                    self.metrics.compressed(payload.len(), compressed.len());
+                    compressed
                 }
+                None => payload,
+            },
+            None => payload,
+        };
+        let payload = match &self.encryption {
+            Some(encryption) => encryption::encrypt(encryption, &message.id, &payload)?,
+            None => payload,
+        };
+        Ok(match &self.signing {
+            Some(signing) => signing::sign(signing, payload),
+            None => payload,
+        })
+    }
+}
 
-                match pubsub.subscribe(&channel).await {
-                    Ok(_) => {
-                        eprintln!(
-                            "[RedisWatcher] Successfully subscribed to channel: {}",
-                            channel
-                        );
-                        // Notify that subscription is ready (similar to Go's WaitGroup.Done())
-                        subscription_ready.notify_waiters();
+// ========== Subscription Worker ==========
+
+/// State owned by the background subscription task
+struct Subscriber<T: Transport> {
+    transport: Arc<T>,
+    channel: String,
+    local_id: String,
+    ignore_self: bool,
+    payload_logging: PayloadLogging,
+    codec: Arc<dyn Codec>,
+    reassembler: Mutex<Reassembler>,
+    reload_on_gap: bool,
+    /// Last sequence number and publish time seen from each sender
+    sequences: Mutex<HashMap<String, (u64, Option<SystemTime>)>>,
+    history: Option<HistoryOptions>,
+    /// Newest history entry seen by the previous replay, `None` before the first
+    history_cursor: Mutex<Option<HistoryId>>,
+    /// Payloads received live since the previous replay
+    live: Mutex<Fingerprints>,
+    /// Payloads replayed by the previous replay and not received live yet
+    replayed: Mutex<Fingerprints>,
+    /// Messages held back by coalescing
+    batch: Option<Mutex<Batch>>,
+    signing: Option<SigningOptions>,
+    encryption: Option<EncryptionOptions>,
+    metrics: Arc<Metrics>,
+    reconnect: ReconnectOptions,
+    callback_concurrency: usize,
+    callback_permits: Arc<Semaphore>,
+    callback_tasks: Arc<Mutex<JoinSet<()>>>,
+    is_closed: Arc<AtomicBool>,
+    callbacks: Arc<Callbacks>,
+    subscription_ready: Arc<watch::Sender<bool>>,
+}
+
+impl<T: Transport> Subscriber<T> {
+    /// Background worker for subscription
+    ///
+    /// Keeps the subscription alive for the lifetime of the watcher:
+    /// whenever the stream ends it resubscribes with backoff, unless the
+    /// retry budget in [`ReconnectOptions`] is exhausted. The backoff only
+    /// resets once a subscription delivered a message or stayed up for
+    /// [`ReconnectOptions::healthy_after`], so a stream that ends right after
+    /// subscribing doesn't turn into a tight reconnect loop.
+    async fn subscription_worker(self) {
+        let mut subscribed_before = false;
+        // Subscribe calls since the subscription was last healthy
+        let mut attempts = 0;
+        // Consecutive failed subscribe calls
+        let mut failures = 0;
+
+        while !self.is_closed.load(Ordering::Relaxed) {
+            match self.transport.subscribe(&self.channel).await {
+                Ok(mut stream) => {
+                    info!("Subscribed to channel {}", self.channel);
+                    // Live messages are buffered in the stream meanwhile
+                    self.replay().await;
+                    // Notify that subscription is ready (similar to Go's WaitGroup.Done())
+                    self.subscription_ready.send_replace(true);
+                    if subscribed_before {
+                        info!("Resubscribed to channel {}", self.channel);
+                        self.metrics.reconnected();
+                        self.emit(WatcherEvent::Reconnected { attempts });
+                    }
+                    subscribed_before = true;
+                    failures = 0;
+
+                    let subscribed_at = Instant::now();
+                    let delivered = self.receive(&mut stream).await;
+                    if self.is_closed.load(Ordering::Relaxed) {
                         break;
                     }
-                    Err(e) => {
-                        subscribe_retry += 1;
-                        eprintln!(
-                            "[RedisWatcher] Failed to subscribe to channel {} (attempt {}): {}",
-                            channel, subscribe_retry, e
+
+                    warn!("Subscription to channel {} lost", self.channel);
+                    self.subscription_ready.send_replace(false);
+                    self.emit(WatcherEvent::Disconnected);
+                    if delivered || subscribed_at.elapsed() >= self.reconnect.healthy_after {
+                        attempts = 0;
+                    } else {
+                        warn!(
+                            "Subscription to channel {} ended before becoming healthy",
+                            self.channel
                         );
-                        if subscribe_retry > 5 {
-                            return Err(e);
+                    }
+                    attempts += 1;
+                    tokio::time::sleep(self.reconnect.delay(attempts)).await;
+                }
+                Err(e) => {
+                    attempts += 1;
+                    failures += 1;
+                    warn!(
+                        "Failed to subscribe to channel {} (attempt {}): {}",
+                        self.channel, failures, e
+                    );
+                    if self
+                        .reconnect
+                        .max_retries
+                        .is_some_and(|max_retries| failures > max_retries)
+                    {
+                        error!(
+                            "Subscription error: giving up on channel {} after {} attempts: {}",
+                            self.channel, failures, e
+                        );
+                        self.emit(WatcherEvent::ReconnectFailed { attempts: failures });
+                        break;
+                    }
+                    tokio::time::sleep(self.reconnect.delay(attempts)).await;
+                }
+            }
+        }
+    }
+
+    /// Deliver messages from `stream` until it ends or the watcher is closed
+    ///
+    /// Messages held back by coalescing are delivered before returning.
+    /// Returns whether any payload was received.
+    async fn receive(&self, stream: &mut PayloadStream) -> bool {
+        let mut delivered = false;
+        // Whether payloads were received since the transport was last told they are handled
+        let mut unsettled = false;
+        loop {
+            // Check if closed before waiting for next message
+            if self.is_closed.load(Ordering::Relaxed) {
+                break;
+            }
+
+            let deadline = self.batch_deadline();
+            let flush_at = deadline.unwrap_or_else(tokio::time::Instant::now);
+            // Use tokio::select! to check for shutdown while waiting
+            tokio::select! {
+                msg_opt = stream.next() => {
+                    match msg_opt {
+                        Some(bytes) => {
+                            delivered = true;
+                            unsettled = true;
+                            let span = span!("receive", channel = %self.channel);
+                            self.handle_payload(&bytes).instrument(span).await
+                        }
+                        None => {
+                            // Stream ended
+                            debug!("Stream of channel {} ended", self.channel);
+                            break;
                         }
-                        tokio::time::sleep(tokio::time::Duration::from_millis(
-                            500 * subscribe_retry,
-                        ))
-                        .await;
                     }
                 }
+                _ = tokio::time::sleep_until(flush_at), if deadline.is_some() => {
+                    // The coalescing window closed
+                    self.flush().await;
+                }
+                _ = tokio::time::sleep(tokio::time::Duration::from_millis(100)) => {
+                    // Periodic check for shutdown
+                    if self.is_closed.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    self.expire_transfers();
+                }
+            }
+            if unsettled && self.settled() {
+                self.transport.acknowledge(&self.channel);
+                unsettled = false;
             }
+        }
+        self.flush().await;
+        if unsettled && self.settled() {
+            self.transport.acknowledge(&self.channel);
+        }
+        delivered
+    }
 
-            let mut stream = pubsub.on_message();
+    /// Whether every payload received so far has been handled
+    ///
+    /// Nothing may be held back by coalescing or waiting for more chunks, and
+    /// no spawned async callback may still be running.
+    fn settled(&self) -> bool {
+        self.batch
+            .as_ref()
+            .is_none_or(|batch| batch.lock().unwrap().is_empty())
+            && self.reassembler.lock().unwrap().is_empty()
+            && self.callback_permits.available_permits() >= self.callback_concurrency.max(1)
+    }
 
-            loop {
-                // Check if closed before waiting for next message
-                if is_closed.load(Ordering::Relaxed) {
-                    break;
+    /// Reassemble, deduplicate and unpack a payload received live
+    async fn handle_payload(&self, bytes: &[u8]) {
+        let received_at = Instant::now();
+        let bytes = match self.reassemble(bytes) {
+            Ok(Some(bytes)) => bytes,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Rejected chunk on channel {}: {}", self.channel, e);
+                self.metrics.parse_error();
+                let payload = String::from_utf8_lossy(bytes).into_owned();
+                with_callback(&self.callbacks.error, |cb| cb(e, payload));
+                return;
+            }
+        };
+        if self.history.is_some() {
+            // Only the payloads of the replay window are compared, so
+            // repeated live messages like bare `Update`s still get through
+            if self.replayed.lock().unwrap().remove(&bytes) {
+                trace!("Dropping replayed message on channel {}", self.channel);
+                self.metrics.duplicate();
+                return;
+            }
+            self.live.lock().unwrap().insert(&bytes);
+        }
+        self.unpack(&bytes, received_at).await;
+    }
+
+    /// Handle a whole payload, unpacking batches
+    async fn unpack(&self, bytes: &[u8], received_at: Instant) {
+        if !batching::is_batch(bytes) {
+            self.handle_message(bytes, received_at).await;
+            return;
+        }
+        match batching::unpack(bytes) {
+            Ok(payloads) => {
+                trace!(
+                    "Unpacking batch of {} messages on channel {}",
+                    payloads.len(),
+                    self.channel
+                );
+                for payload in payloads {
+                    self.handle_message(payload, received_at).await;
+                }
+            }
+            Err(e) => {
+                warn!("Rejected batch on channel {}: {}", self.channel, e);
+                self.metrics.parse_error();
+                let payload = String::from_utf8_lossy(bytes).into_owned();
+                with_callback(&self.callbacks.error, |cb| cb(e, payload));
+            }
+        }
+    }
+
+    /// Open, decode and filter a single message, then hand it to the callbacks
+    async fn handle_message(&self, bytes: &[u8], received_at: Instant) {
+        self.metrics.received();
+
+        let bytes = match self.open(bytes) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!("Rejected message on channel {}: {}", self.channel, e);
+                match e {
+                    WatcherError::InvalidSignature(_) => self.metrics.invalid_signature(),
+                    WatcherError::Compression(_) => self.metrics.parse_error(),
+                    _ => self.metrics.decryption_failure(),
                 }
+                let payload = String::from_utf8_lossy(bytes).into_owned();
+                with_callback(&self.callbacks.error, |cb| cb(e, payload));
+                return;
+            }
+        };
+        let parsed = codec::decode(self.codec.as_ref(), &bytes);
+        debug!(
+            "Received message on channel {}: {}",
+            self.channel,
+            Payload::new(
+                self.payload_logging,
+                &bytes,
+                parsed.as_ref().ok().map(|envelope| &envelope.payload)
+            )
+        );
+        let published_at = parsed
+            .as_ref()
+            .ok()
+            .filter(|envelope| envelope.timestamp > 0)
+            .map(|envelope| UNIX_EPOCH + Duration::from_millis(envelope.timestamp));
+        let payload = String::from_utf8_lossy(&bytes).into_owned();
+        let (parsed, payload) = match parsed {
+            // The string callback gets a bare JSON message whatever the wire format
+            Ok(envelope) if envelope.version > 0 || !codec::is_json(&bytes) => {
+                let payload = envelope.payload.to_json().unwrap_or(payload);
+                (Ok(envelope.payload), payload)
+            }
+            parsed => (parsed.map(|envelope| envelope.payload), payload),
+        };
 
-                // Use tokio::select! to check for shutdown while waiting
-                tokio::select! {
-                    msg_opt = stream.next() => {
-                        match msg_opt {
-                            Some(msg) => {
-                                let payload: String = msg.get_payload().unwrap_or_default();
-                                eprintln!("[RedisWatcher] Received message on channel {}: {}", channel, payload);
-
-                                // Parse message and check if we should ignore it
-                                if ignore_self {
-                                    if let Ok(parsed_msg) = Message::from_json(&payload) {
-                                        if parsed_msg.id == local_id {
-                                            eprintln!("[RedisWatcher] Ignoring self message from: {}", parsed_msg.id);
-                                            continue;
-                                        }
-                                    }
-                                }
-
-                                // Call callback
-                                if let Ok(mut cb_guard) = callback.lock() {
-                                    if let Some(ref mut cb) = *cb_guard {
-                                        eprintln!("[RedisWatcher] Invoking callback for message");
-                                        cb(payload);
-                                    } else {
-                                        eprintln!("[RedisWatcher] Callback not set, message ignored");
-                                    }
-                                } else {
-                                    eprintln!("[RedisWatcher] Failed to acquire callback lock");
-                                }
-                            }
-                            None => {
-                                // Stream ended
-                                eprintln!("[RedisWatcher] Pubsub stream ended");
-                                break;
-                            }
-                        }
+        // Check if we should ignore it
+        if let Ok(parsed_msg) = &parsed {
+            if self.ignore_self && parsed_msg.id == self.local_id {
+                trace!("Ignoring self message from {}", parsed_msg.id);
+                self.metrics.ignored_self();
+                return;
+            }
+        }
+
+        // Replace the message by a full reload if messages from its sender were
+        // missed, or if it may be an old signed message sent again
+        let (parsed, payload) = match parsed {
+            Ok(message)
+                if match self.detect_gap(&message, published_at) {
+                    Sequence::InOrder => false,
+                    Sequence::Gap => self.reload_on_gap,
+                    Sequence::Stale => true,
+                } =>
+            {
+                let mut reload = Message::new(UpdateType::Update, message.id);
+                reload.sequence = message.sequence;
+                let payload = reload.to_json().unwrap_or(payload);
+                (Ok(reload), payload)
+            }
+            parsed => (parsed, payload),
+        };
+
+        match parsed {
+            Ok(message) => {
+                let full = match &self.batch {
+                    Some(batch) => {
+                        batch
+                            .lock()
+                            .unwrap()
+                            .push((message, payload), received_at, published_at)
                     }
-                    _ = tokio::time::sleep(tokio::time::Duration::from_millis(100)) => {
-                        // Periodic check for shutdown
-                        if is_closed.load(Ordering::Relaxed) {
-                            break;
-                        }
+                    None => {
+                        self.deliver(vec![(message, payload)], received_at, published_at)
+                            .await;
+                        return;
                     }
+                };
+                if full {
+                    self.flush().await;
                 }
             }
+            Err(e) => {
+                warn!("Failed to parse message on channel {}: {}", self.channel, e);
+                self.metrics.parse_error();
+                let span = span!("callback", channel = %self.channel);
+                let handled = span.in_scope(|| {
+                    with_callback(&self.callbacks.update, |cb| cb(payload.clone()))
+                        | with_callback(&self.callbacks.error, |cb| cb(e, payload))
+                });
+                self.handled(handled, received_at, None);
+            }
+        }
+    }
 
-            Ok::<(), redis::RedisError>(())
+    /// Hand received messages to the callbacks
+    ///
+    /// With coalescing, the per-message callbacks run once for the whole
+    /// batch, with the messages merged into a full reload.
+    async fn deliver(
+        &self,
+        received: Vec<Received>,
+        received_at: Instant,
+        published_at: Option<SystemTime>,
+    ) {
+        let span = span!("callback", channel = %self.channel);
+        let mut handled = false;
+        let messages: Vec<Message> = received
+            .iter()
+            .map(|(message, _)| message.clone())
+            .collect();
+        let individual = match &self.batch {
+            Some(_) => coalescing::merge(received).into_iter().collect(),
+            None => received,
         };
+        for (message, payload) in individual {
+            handled |= span.in_scope(|| with_callback(&self.callbacks.update, |cb| cb(payload)));
+            handled |= self.invoke_async(message.clone(), span.clone()).await;
+            handled |= span.in_scope(|| with_callback(&self.callbacks.message, |cb| cb(message)));
+        }
+        handled |= span.in_scope(|| with_callback(&self.callbacks.batch, |cb| cb(messages)));
+        self.handled(handled, received_at, published_at);
+    }
 
-        if let Err(e) = result.await {
-            log::error!("Subscription error: {}", e);
+    /// Deliver the messages held back by coalescing, if any
+    async fn flush(&self) {
+        let Some(batch) = &self.batch else {
+            return;
+        };
+        let taken = batch.lock().unwrap().take();
+        if let Some((received, received_at, published_at)) = taken {
+            trace!(
+                "Delivering {} coalesced messages on channel {}",
+                received.len(),
+                self.channel
+            );
+            self.deliver(received, received_at, published_at).await;
         }
     }
+
+    /// When the messages held back by coalescing are due
+    fn batch_deadline(&self) -> Option<tokio::time::Instant> {
+        self.batch
+            .as_ref()
+            .and_then(|batch| batch.lock().unwrap().deadline())
+    }
+
+    fn handled(&self, handled: bool, received_at: Instant, published_at: Option<SystemTime>) {
+        if handled {
+            self.metrics.callback_invoked(received_at.elapsed());
+            if let Some(published_at) = published_at {
+                // A sender clock ahead of ours counts as no delay
+                let latency = SystemTime::now()
+                    .duration_since(published_at)
+                    .unwrap_or_default();
+                self.metrics.delivered(latency);
+            }
+        } else {
+            debug!(
+                "No callback set, message on channel {} ignored",
+                self.channel
+            );
+        }
+    }
+
+    /// Handle the messages from the history that were published while unsubscribed
+    ///
+    /// Entries appended after the newest one seen by the previous replay are
+    /// replayed, except those already received live since then. The first
+    /// replay only covers entries published since
+    /// [`HistoryOptions::replay_since`], if set.
+    async fn replay(&self) {
+        let Some(history) = &self.history else {
+            return;
+        };
+        let entries = match self.transport.read_history(&self.channel).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!(
+                    "Failed to read the history of channel {}: {}",
+                    self.channel, e
+                );
+                return;
+            }
+        };
+
+        let cursor = *self.history_cursor.lock().unwrap();
+        self.replayed.lock().unwrap().clear();
+        let mut replayed = 0;
+        for (id, entry) in &entries {
+            let Some((published_at, payload)) = replay::parse(entry) else {
+                warn!(
+                    "Skipping malformed history entry on channel {}",
+                    self.channel
+                );
+                continue;
+            };
+            let missed = match cursor {
+                Some(cursor) => *id > cursor,
+                None => history
+                    .replay_since
+                    .is_some_and(|replay_since| published_at >= replay_since),
+            };
+            if !missed {
+                continue;
+            }
+            if self.live.lock().unwrap().remove(payload) {
+                trace!(
+                    "Skipping history entry already received on channel {}",
+                    self.channel
+                );
+                continue;
+            }
+            // The live copy may still be buffered in the new subscription
+            self.replayed.lock().unwrap().insert(payload);
+            self.metrics.replayed();
+            replayed += 1;
+            let span = span!("replay", channel = %self.channel);
+            self.unpack(payload, Instant::now()).instrument(span).await;
+        }
+
+        // Transports assign IDs above 0-0, so an empty history replays everything next time
+        let newest = entries.last().map_or(HistoryId(0, 0), |(id, _)| *id);
+        *self.history_cursor.lock().unwrap() =
+            Some(cursor.map_or(newest, |cursor| cursor.max(newest)));
+        self.live.lock().unwrap().clear();
+        if replayed > 0 {
+            info!(
+                "Replayed {} missed messages on channel {}",
+                replayed, self.channel
+            );
+        }
+    }
+
+    /// Collect chunks until a payload is complete
+    ///
+    /// Returns `None` while chunks of the payload are missing.
+    fn reassemble<'a>(&self, bytes: &'a [u8]) -> Result<Option<Cow<'a, [u8]>>> {
+        self.expire_transfers();
+        if !chunking::is_chunk(bytes) {
+            return Ok(Some(Cow::Borrowed(bytes)));
+        }
+
+        let mut reassembler = self.reassembler.lock().unwrap();
+        let reassembled = reassembler.push(bytes);
+        let evicted = reassembler.take_evicted();
+        if evicted > 0 {
+            warn!(
+                "Evicted {} incomplete chunked messages on channel {} to stay within limits",
+                evicted, self.channel
+            );
+            for _ in 0..evicted {
+                self.metrics.evicted_transfer();
+            }
+        }
+        Ok(reassembled?.map(Cow::Owned))
+    }
+
+    /// Discard chunked messages that were not completed in time
+    fn expire_transfers(&self) {
+        let expired = self.reassembler.lock().unwrap().expire();
+        if expired > 0 {
+            warn!(
+                "Discarded {} incomplete chunked messages on channel {}",
+                expired, self.channel
+            );
+            for _ in 0..expired {
+                self.metrics.incomplete_transfer();
+            }
+        }
+    }
+
+    /// Verify, decrypt and decompress a received payload
+    fn open(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let bytes = match &self.signing {
+            Some(signing) => signing::verify(signing, bytes)?,
+            None => bytes,
+        };
+        let bytes = match &self.encryption {
+            Some(encryption) => encryption::decrypt(encryption, bytes)?,
+            None => bytes.to_vec(),
+        };
+        compression::decompress(bytes)
+    }
+
+    /// Run the async callback, inline or spawned depending on the concurrency limit
+    async fn invoke_async(&self, message: Message, span: Span) -> bool {
+        // Clone the callback out so the lock isn't held across the await
+        let Some((cb, in_order)) = self.callbacks.message_async.lock().unwrap().clone() else {
+            return false;
+        };
+
+        let future = cb(message).instrument(span);
+        if in_order || self.callback_concurrency <= 1 {
+            // Await in place to keep message order
+            future.await;
+        } else if let Ok(permit) = self.callback_permits.clone().acquire_owned().await {
+            let mut tasks = self.callback_tasks.lock().unwrap();
+            // Reap finished invocations so the set stays bounded
+            while tasks.try_join_next().is_some() {}
+            tasks.spawn(async move {
+                future.await;
+                drop(permit);
+            });
+        }
+        true
+    }
+
+    /// Track the sequence number of `message`, reporting missed messages
+    ///
+    /// With signing, a message numbered at or before the last one from its
+    /// sender is stale unless its envelope shows it was published later: a
+    /// valid signature doesn't tell an old message sent again from a new one.
+    fn detect_gap(&self, message: &Message, published_at: Option<SystemTime>) -> Sequence {
+        if message.sequence == 0 {
+            return Sequence::InOrder;
+        }
+        let mut sequences = self.sequences.lock().unwrap();
+        let last = sequences.get(&message.id).copied();
+        if let Some((last, last_published_at)) = last {
+            let newer = published_at.is_some_and(|published_at| {
+                last_published_at.is_none_or(|last_published_at| published_at > last_published_at)
+            });
+            if self.signing.is_some() && message.sequence <= last && !newer {
+                // Keep the last sequence so a later resend isn't taken as the next message
+                drop(sequences);
+                warn!(
+                    "Stale signed message {} from {} on channel {}, reloading instead",
+                    message.sequence, message.id, self.channel
+                );
+                self.metrics.stale_message();
+                return Sequence::Stale;
+            }
+        }
+        sequences.insert(message.id.clone(), (message.sequence, published_at));
+        drop(sequences);
+
+        let event = match last {
+            // Nothing is known about messages sent before we subscribed
+            None => return Sequence::InOrder,
+            // Redelivered by an at-least-once transport
+            Some((last, _)) if message.sequence == last && self.signing.is_none() => {
+                return Sequence::InOrder
+            }
+            Some((last, _)) if message.sequence == last + 1 => return Sequence::InOrder,
+            Some((last, _)) if message.sequence > last => WatcherEvent::GapDetected {
+                sender: message.id.clone(),
+                expected: last + 1,
+                received: message.sequence,
+            },
+            Some((last, _)) => WatcherEvent::SenderRestarted {
+                sender: message.id.clone(),
+                last,
+                received: message.sequence,
+            },
+        };
+        warn!("Missed messages on channel {}: {:?}", self.channel, event);
+        self.metrics.sequence_gap();
+        self.emit(event);
+        Sequence::Gap
+    }
+
+    fn emit(&self, event: WatcherEvent) {
+        with_callback(&self.callbacks.event, |cb| cb(event));
+    }
 }
 
-impl Watcher for RedisWatcher {
+impl<T: Transport> Watcher for RedisWatcher<T> {
     fn set_update_callback(&mut self, cb: Box<dyn FnMut(String) + Send + Sync>) {
-        eprintln!("[RedisWatcher] Setting update callback");
-        *self.callback.lock().unwrap() = Some(cb);
+        debug!("Setting update callback");
+        *self.callbacks.update.lock().unwrap() = Some(cb);
 
         // Note: Unlike the old implementation, we don't restart subscription here
         // because subscription is already started in new()/new_cluster()
@@ -608,15 +1860,14 @@ impl Watcher for RedisWatcher {
 
     fn update(&mut self, d: EventData) {
         let message = event_data_to_message(&d, &self.options.local_id);
-        eprintln!(
-            "[RedisWatcher] update() called with event: {:?}",
-            message.method
-        );
-        let _ = self.publish_message(&message);
+        trace!("Queueing {} message", message.method);
+        if let Err(e) = self.publish_message(&message) {
+            warn!("Dropping {} message: {}", message.method, e);
+        }
     }
 }
 
-impl Drop for RedisWatcher {
+impl<T: Transport> Drop for RedisWatcher<T> {
     fn drop(&mut self) {
         // Signal closure first
         self.is_closed.store(true, Ordering::Relaxed);
@@ -650,6 +1901,40 @@ mod tests {
         assert_eq!(message.id, parsed.id);
     }
 
+    #[test]
+    fn test_unknown_update_type_roundtrip() {
+        let json = r#"{"Method":"UpdateForSomethingNew","ID":"node-2"}"#;
+        let message = Message::from_json(json).unwrap();
+        assert_eq!(
+            message.method,
+            UpdateType::Unknown("UpdateForSomethingNew".to_string())
+        );
+        assert!(message.to_json().unwrap().contains("UpdateForSomethingNew"));
+    }
+
+    #[test]
+    fn test_envelope_decoding() {
+        let message = Message::new(UpdateType::UpdateForSavePolicy, "node-1".to_string());
+
+        let envelope =
+            Envelope::decode(&Envelope::new(message.clone()).to_json().unwrap()).unwrap();
+        assert_eq!(envelope.version, Envelope::VERSION);
+        assert_eq!(envelope.sender, "node-1");
+        assert!(envelope.timestamp > 0);
+        assert_eq!(envelope.payload.method, UpdateType::UpdateForSavePolicy);
+
+        // Bare messages decode as version 0
+        let bare = Envelope::decode(&message.to_json().unwrap()).unwrap();
+        assert_eq!(bare.version, 0);
+        assert_eq!(bare.payload.id, "node-1");
+
+        // Newer envelopes with extra fields are still understood
+        let newer = r#"{"Version":7,"Sender":"node-3","Extra":true,"Payload":{"Method":"Update","ID":"node-3","Shiny":1}}"#;
+        let newer = Envelope::decode(newer).unwrap();
+        assert_eq!(newer.version, 7);
+        assert_eq!(newer.payload.method, UpdateType::Update);
+    }
+
     #[test]
     fn test_event_data_conversion() {
         let event = EventData::AddPolicy(